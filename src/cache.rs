@@ -0,0 +1,299 @@
+// Content-addressed cache of downloaded mod jars in CACHE_DIR/mods, so reinstalling a profile
+// (or installing it on a machine that shares the cache) only fetches each jar once. Entries are
+// keyed by the strongest hash the manifest gives for a mod. Mods without a hash aren't cached, since
+// the file behind their url can change, and every restored jar is checked against the hashes again.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::hashes::Verifier;
+use crate::settings::settings;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+lazy_static! {
+    // Mods download concurrently, this stops them writing the index over each other
+    static ref INDEX_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// The lock only guards the files, so one held by a thread that panicked is still fine to take
+fn lock_index() -> MutexGuard<'static, ()> {
+    INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CacheEntry {
+    pub key: String,
+    pub url: String,
+    pub size: u64,
+    // Unix timestamp of when the entry was last stored or used
+    pub last_used: i64
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheIndex {
+    entries: Vec<CacheEntry>
+}
+
+impl CacheIndex {
+    fn path() -> PathBuf {
+        cache_dir().join("index.json")
+    }
+
+    fn load() -> Result<CacheIndex> {
+        let path = CacheIndex::path();
+        if !path.exists() {
+            return Ok(CacheIndex::default())
+        }
+        let data = std::fs::read_to_string(&path).map_err(|err| ModtoolError::io(&path, err))?;
+        Ok(serde_json::from_str(data.as_str())?)
+    }
+
+    fn save(&self) -> Result<()> {
+        let dir = cache_dir();
+        std::fs::create_dir_all(&dir).map_err(|err| ModtoolError::io(&dir, err))?;
+        // Written next to the index and renamed over it, so a crash never leaves half an index
+        let path = CacheIndex::path();
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?).map_err(|err| ModtoolError::io(&tmp_path, err))?;
+        std::fs::rename(&tmp_path, &path).map_err(|err| ModtoolError::io(&path, err))
+    }
+
+    fn size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+
+    // Drops least recently used entries until the cache fits in `limit` bytes
+    fn evict(&mut self, limit: u64) -> Result<(usize, u64)> {
+        self.entries.sort_by_key(|entry| entry.last_used);
+        let mut removed = 0;
+        let mut freed = 0;
+        while self.size() > limit && !self.entries.is_empty() {
+            let entry = self.entries.remove(0);
+            let path = entry_path(&entry.key);
+            if path.exists() {
+                std::fs::remove_file(&path).map_err(|err| ModtoolError::io(&path, err))?;
+            }
+            removed += 1;
+            freed += entry.size;
+        }
+        Ok((removed, freed))
+    }
+}
+
+pub fn cache_dir() -> PathBuf {
+    Path::new(CACHE_DIR.as_str()).join("mods")
+}
+
+// None for mods the manifest gives no hash for, which aren't cached
+fn cache_key(modinfo: &ModDownload) -> Option<String> {
+    if let Some(hash) = modinfo.sha512.as_ref() {
+        return Some(format!("sha512-{}", hash.to_lowercase()))
+    }
+    if let Some(hash) = modinfo.sha256.as_ref() {
+        return Some(format!("sha256-{}", hash.to_lowercase()))
+    }
+    modinfo.sha1.as_ref().map(|hash| format!("sha1-{}", hash.to_lowercase()))
+}
+
+fn entry_path(key: &str) -> PathBuf {
+    cache_dir().join(format!("{}.jar", key))
+}
+
+// Whether a cached jar still matches the manifest's hashes
+fn matches_hashes(modinfo: &ModDownload, path: &Path) -> Result<bool> {
+    let mut verifier = Verifier::new(&modinfo.sha1, &modinfo.sha256, &modinfo.sha512);
+    verifier.update_from_file(path).map_err(|err| ModtoolError::io(path, err))?;
+    Ok(verifier.verify().is_ok())
+}
+
+// Hardlinks `from` to `to`, or copies it when they're on different filesystems
+fn link_or_copy(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        std::fs::remove_file(to).map_err(|err| ModtoolError::io(to, err))?;
+    }
+    if std::fs::hard_link(from, to).is_err() {
+        std::fs::copy(from, to).map_err(|err| ModtoolError::io(to, err))?;
+    }
+    Ok(())
+}
+
+// Puts the cached copy of a mod at `target`. Returns its size, or None if it isn't cached.
+pub fn restore(modinfo: &ModDownload, target: &Path) -> Result<Option<u64>> {
+    let _lock = lock_index();
+    let key = match cache_key(modinfo) {
+        Some(key) => key,
+        None => return Ok(None)
+    };
+    let mut index = CacheIndex::load()?;
+    let position = match index.entries.iter().position(|entry| entry.key == key) {
+        Some(position) => position,
+        None => return Ok(None)
+    };
+
+    let path = entry_path(&key);
+    if !path.exists() {
+        index.entries.remove(position);
+        index.save()?;
+        return Ok(None)
+    }
+    // A truncated or changed jar is thrown away, and the mod downloaded again
+    if !matches_hashes(modinfo, &path)? {
+        log::warn!("Cached jar for {} doesn't match its hashes, removing it", modinfo.name);
+        std::fs::remove_file(&path).map_err(|err| ModtoolError::io(&path, err))?;
+        index.entries.remove(position);
+        index.save()?;
+        return Ok(None)
+    }
+    link_or_copy(&path, target)?;
+
+    let entry = &mut index.entries[position];
+    entry.last_used = Utc::now().timestamp();
+    let size = entry.size;
+    index.save()?;
+    Ok(Some(size))
+}
+
+// Adds a verified download to the cache, then evicts old entries to stay under the size limit.
+// The jar is copied rather than linked, so changing the one in the mods folder can't change the cache.
+pub fn store(modinfo: &ModDownload, source: &Path) -> Result<()> {
+    let _lock = lock_index();
+    let key = match cache_key(modinfo) {
+        Some(key) => key,
+        None => return Ok(())
+    };
+    let dir = cache_dir();
+    std::fs::create_dir_all(&dir).map_err(|err| ModtoolError::io(&dir, err))?;
+
+    let path = entry_path(&key);
+    let tmp_path = path.with_extension("jar.tmp");
+    std::fs::copy(source, &tmp_path).map_err(|err| ModtoolError::io(&tmp_path, err))?;
+    std::fs::rename(&tmp_path, &path).map_err(|err| ModtoolError::io(&path, err))?;
+    let size = std::fs::metadata(&path).map_err(|err| ModtoolError::io(&path, err))?.len();
+
+    let mut index = CacheIndex::load()?;
+    index.entries.retain(|entry| entry.key != key);
+    index.entries.push(CacheEntry {
+        key,
+        url: modinfo.url.clone(),
+        size,
+        last_used: Utc::now().timestamp()
+    });
+    index.evict(settings().cache_limit_mb * 1000000)?;
+    index.save()
+}
+
+// Removes least recently used jars until the cache is at most `limit_mb`.
+// Returns how many were removed and how many bytes that freed.
+pub fn evict(limit_mb: u64) -> Result<(usize, u64)> {
+    let _lock = lock_index();
+    let mut index = CacheIndex::load()?;
+    let res = index.evict(limit_mb * 1000000)?;
+    index.save()?;
+    Ok(res)
+}
+
+// Number of cached jars and their total size in bytes
+pub fn usage() -> Result<(usize, u64)> {
+    let _lock = lock_index();
+    let index = CacheIndex::load()?;
+    Ok((index.entries.len(), index.size()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashes::file_sha1;
+    use crate::test_support;
+
+    fn download(name: &str, hashes: serde_json::Value) -> ModDownload {
+        let mut modinfo = serde_json::json!({ "name": name, "url": format!("https://example.com/{}.jar", name), "provider": "Modrinth" });
+        modinfo.as_object_mut().unwrap().extend(hashes.as_object().unwrap().clone());
+        install::mod_download(&serde_json::from_value(modinfo).unwrap())
+    }
+
+    // A jar in its own folder with the sha1 the manifest would give for it
+    fn jar(name: &str, contents: &str) -> (ModDownload, PathBuf) {
+        let path = test_support::scratch_dir(name).join(format!("{}.jar", name));
+        std::fs::write(&path, contents).unwrap();
+        (download(name, serde_json::json!({ "sha1": file_sha1(&path).unwrap() })), path)
+    }
+
+    fn cached(key: &str) -> bool {
+        let _lock = lock_index();
+        CacheIndex::load().unwrap().entries.iter().any(|entry| entry.key == key)
+    }
+
+    #[test]
+    fn keys_use_the_strongest_hash() {
+        let all = download("all", serde_json::json!({ "sha1": "AA", "sha256": "BB", "sha512": "CC" }));
+        assert_eq!(cache_key(&all).as_deref(), Some("sha512-cc"));
+        let no_sha512 = download("no-sha512", serde_json::json!({ "sha1": "aa", "sha256": "bb" }));
+        assert_eq!(cache_key(&no_sha512).as_deref(), Some("sha256-bb"));
+        let sha1 = download("sha1", serde_json::json!({ "sha1": "aa" }));
+        assert_eq!(cache_key(&sha1).as_deref(), Some("sha1-aa"));
+        assert_eq!(cache_key(&download("none", serde_json::json!({}))), None);
+    }
+
+    #[test]
+    fn eviction_removes_least_recently_used_first() {
+        test_support::home();
+        std::fs::create_dir_all(cache_dir()).unwrap();
+        let mut index = CacheIndex::default();
+        for (key, last_used) in [("evict-newest", 300), ("evict-oldest", 100), ("evict-middle", 200)] {
+            std::fs::write(entry_path(key), "0123456789").unwrap();
+            index.entries.push(CacheEntry { key: String::from(key), url: String::new(), size: 10, last_used });
+        }
+
+        assert_eq!(index.evict(30).unwrap(), (0, 0));
+        assert_eq!(index.evict(15).unwrap(), (2, 20));
+        let left: Vec<&str> = index.entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(left, vec!["evict-newest"]);
+        assert!(!entry_path("evict-oldest").exists());
+        assert!(!entry_path("evict-middle").exists());
+        assert!(entry_path("evict-newest").exists());
+    }
+
+    #[test]
+    fn restores_a_stored_jar_as_a_separate_copy() {
+        test_support::home();
+        let (modinfo, source) = jar("cache-roundtrip", "roundtrip jar");
+        store(&modinfo, &source).unwrap();
+        // Changing the jar in the mods folder mustn't reach the cache
+        std::fs::write(&source, "edited in place").unwrap();
+
+        let target = source.with_file_name("restored.jar");
+        assert_eq!(restore(&modinfo, &target).unwrap(), Some(13));
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "roundtrip jar");
+
+        let unhashed = download("cache-unhashed", serde_json::json!({}));
+        store(&unhashed, &source).unwrap();
+        assert_eq!(restore(&unhashed, &target).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_files_drop_their_entry() {
+        test_support::home();
+        let (modinfo, source) = jar("cache-missing", "missing jar");
+        store(&modinfo, &source).unwrap();
+        let key = cache_key(&modinfo).unwrap();
+        std::fs::remove_file(entry_path(&key)).unwrap();
+
+        assert_eq!(restore(&modinfo, &source.with_file_name("restored.jar")).unwrap(), None);
+        assert!(!cached(&key));
+    }
+
+    #[test]
+    fn corrupt_entries_are_not_restored() {
+        test_support::home();
+        let (modinfo, source) = jar("cache-corrupt", "corrupt jar");
+        store(&modinfo, &source).unwrap();
+        let key = cache_key(&modinfo).unwrap();
+        std::fs::write(entry_path(&key), "corrupt j").unwrap();
+
+        let target = source.with_file_name("restored.jar");
+        assert_eq!(restore(&modinfo, &target).unwrap(), None);
+        assert!(!target.exists());
+        assert!(!entry_path(&key).exists());
+        assert!(!cached(&key));
+    }
+}
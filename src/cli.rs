@@ -0,0 +1,398 @@
+// Headless mode: `modtool-rs install --profile <id|name>` runs the same steps as the
+// GUI pages, printing progress to stdout instead of opening a window.
+
+use crate::*;
+use crate::error::{self, ModtoolError};
+use crate::dependencies::MissingDependency;
+use crate::downloads::{DownloadEvent, DownloadManager, Job};
+use crate::install::*;
+use crate::manifest::fetch_manifest;
+use crate::settings::SETTINGS;
+
+static USAGE: &str = "Usage:
+    modtool-rs                                  Start the graphical installer
+    modtool-rs install --profile <id|name>      Install a profile without opening a window
+               [--add-dependencies]             Also download missing dependencies from Modrinth
+    modtool-rs import <file>                    Add a Modrinth .mrpack or CurseForge modpack zip to
+                                                the local profiles
+    modtool-rs export --profile <id|name> <file>
+               [--config <folder>]              Save an installed profile as a Modrinth .mrpack, with
+                                                these folders from its game directory as overrides
+    modtool-rs backups                          List backups of the mods folder
+    modtool-rs restore [<backup>]               Restore the newest backup, or the named one
+    modtool-rs cache                            Show how much space the mod cache uses
+    modtool-rs cache prune [--limit <MB>]       Remove least recently used mods from the cache until
+                                                it fits the limit (cache_limit_mb from settings.json
+                                                by default, 0 empties it)
+    modtool-rs help                             Show this message
+
+Options:
+    --manifest <url|path>                       Load profiles from this manifest instead of the
+                                                sources in settings.json. Can be given more than once";
+
+enum Command {
+    Install { profile: String, add_dependencies: bool },
+    Import { path: String },
+    Export { profile: String, output: String, config_dirs: Vec<String> },
+    Backups,
+    Restore { backup: Option<String> },
+    Cache,
+    PruneCache { limit_mb: Option<u64> },
+    Help
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(|arg| arg.as_str()) {
+        Some("install") => {
+            let mut profile = None;
+            let mut add_dependencies = false;
+            let mut iter = args[1..].iter();
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--profile" | "-p" => {
+                        profile = Some(iter.next().ok_or(String::from("--profile needs a value"))?.clone());
+                    },
+                    "--add-dependencies" => add_dependencies = true,
+                    other => return Err(format!("Unknown argument '{}'", other))
+                }
+            }
+            match profile {
+                Some(profile) => Ok(Command::Install { profile, add_dependencies }),
+                None => Err(String::from("install needs a --profile <id|name>"))
+            }
+        },
+        Some("import") => match args.len() {
+            2 => Ok(Command::Import { path: args[1].clone() }),
+            _ => Err(String::from("import needs exactly one modpack file"))
+        },
+        Some("export") => {
+            let mut profile = None;
+            let mut output = None;
+            let mut config_dirs = Vec::new();
+            let mut iter = args[1..].iter();
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--profile" | "-p" => {
+                        profile = Some(iter.next().ok_or(String::from("--profile needs a value"))?.clone());
+                    },
+                    "--config" => config_dirs.push(iter.next().ok_or(String::from("--config needs a folder"))?.clone()),
+                    other if other.starts_with('-') => return Err(format!("Unknown argument '{}'", other)),
+                    other if output.is_none() => output = Some(String::from(other)),
+                    other => return Err(format!("Unexpected argument '{}'", other))
+                }
+            }
+            match (profile, output) {
+                (Some(profile), Some(output)) => Ok(Command::Export { profile, output, config_dirs }),
+                _ => Err(String::from("export needs a --profile <id|name> and a file to write"))
+            }
+        },
+        Some("backups") => Ok(Command::Backups),
+        Some("restore") => match args.len() {
+            1 => Ok(Command::Restore { backup: None }),
+            2 => Ok(Command::Restore { backup: Some(args[1].clone()) }),
+            _ => Err(String::from("restore takes at most one backup name"))
+        },
+        Some("cache") => match args.get(1).map(|arg| arg.as_str()) {
+            None => Ok(Command::Cache),
+            Some("prune") => match args.len() {
+                2 => Ok(Command::PruneCache { limit_mb: None }),
+                4 if args[2] == "--limit" => match args[3].parse() {
+                    Ok(limit_mb) => Ok(Command::PruneCache { limit_mb: Some(limit_mb) }),
+                    Err(_) => Err(format!("--limit needs a size in MB, not '{}'", args[3]))
+                },
+                _ => Err(String::from("cache prune only takes --limit <MB>"))
+            },
+            Some(other) => Err(format!("Unknown cache command '{}'", other))
+        },
+        Some("help") | Some("--help") | Some("-h") => Ok(Command::Help),
+        Some(other) => Err(format!("Unknown command '{}'", other)),
+        None => Ok(Command::Help)
+    }
+}
+
+// Strips the options that apply to every mode (including the GUI) and returns the rest
+pub fn take_global_args(args: Vec<String>) -> Result<Vec<String>, String> {
+    let mut rest = Vec::new();
+    let mut manifest_sources = Vec::new();
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--manifest" | "-m" => {
+                manifest_sources.push(iter.next().ok_or(String::from("--manifest needs a value"))?);
+            },
+            _ => rest.push(arg)
+        }
+    }
+    if !manifest_sources.is_empty() {
+        SETTINGS.write().unwrap().manifest_sources = manifest_sources;
+    }
+    Ok(rest)
+}
+
+pub fn usage_error(err: String) -> i32 {
+    attach_console();
+    eprintln!("{}\n\n{}", err, USAGE);
+    2
+}
+
+// Returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    attach_console();
+
+    let command = match parse_args(args) {
+        Ok(command) => command,
+        Err(err) => return usage_error(err)
+    };
+
+    match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            0
+        },
+        Command::Install { profile, add_dependencies } => {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            exit_code(runtime.block_on(install(profile, add_dependencies)))
+        },
+        Command::Import { path } => exit_code(import(path)),
+        Command::Export { profile, output, config_dirs } => {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            exit_code(runtime.block_on(export(profile, output, config_dirs)))
+        },
+        Command::Backups => exit_code(list_backups()),
+        Command::Restore { backup } => exit_code(restore(backup)),
+        Command::Cache => exit_code(show_cache()),
+        Command::PruneCache { limit_mb } => exit_code(prune_cache(limit_mb))
+    }
+}
+
+fn exit_code(res: error::Result<()>) -> i32 {
+    match res {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}: {}", err.title(), err);
+            if let Some(url) = err.help_url() {
+                eprintln!("See {}", url);
+            }
+            1
+        }
+    }
+}
+
+fn import(path: String) -> error::Result<()> {
+    let report = packs::import_pack(Path::new(path.as_str()))?;
+    let meta = &report.profile.meta;
+    println!("Imported {} (id {}) with {} mods, for Minecraft {}", meta.name, meta.id, report.profile.mods.len(), meta.version);
+    for entry in report.skipped.iter() {
+        println!("Skipped {}", entry);
+    }
+    if !report.unresolved.is_empty() {
+        println!("{} mods only have a CurseForge id and are looked up when installing:", report.unresolved.len());
+        for entry in report.unresolved.iter() {
+            println!("    {}", entry);
+        }
+        if settings::settings().curseforge_api_key.is_none() {
+            println!("That needs curseforge_api_key in {}", settings::Settings::path().display());
+        }
+    }
+    println!("Install it with: modtool-rs install --profile {}", meta.id);
+    Ok(())
+}
+
+async fn export(profile_query: String, output: String, config_dirs: Vec<String>) -> error::Result<()> {
+    let manifest = fetch_manifest().await?;
+    let profile = manifest.find(profile_query.as_str())
+        .ok_or(ModtoolError::Manifest(format!("No profile with id or name '{}' in the manifest", profile_query)))?;
+    let report = packs::export_mrpack(&profile, &config_dirs, Path::new(output.as_str()))?;
+    println!("Exported {} to {}: {} mods, {} override files", profile.meta.name, output, report.listed, report.overrides);
+    for filename in report.bundled.iter() {
+        println!("{} has no download url, so it's included in the pack", filename);
+    }
+    Ok(())
+}
+
+fn list_backups() -> error::Result<()> {
+    let backups = mods_dir::list_backups()?;
+    if backups.is_empty() {
+        println!("No backups in {}", mods_dir::backups_dir().display());
+    }
+    for backup in backups {
+        println!("{}", backup.file_name().unwrap_or_default().to_string_lossy());
+    }
+    Ok(())
+}
+
+fn restore(name: Option<String>) -> error::Result<()> {
+    let backups = mods_dir::list_backups()?;
+    let backup = match name {
+        Some(name) => backups.into_iter().find(|backup| backup.file_name().map(|file| file.to_string_lossy() == name).unwrap_or(false)),
+        None => backups.into_iter().next()
+    };
+    let backup = backup.ok_or(ModtoolError::Manifest(format!("No matching backup in {}", mods_dir::backups_dir().display())))?;
+    let (restored, target) = mods_dir::restore_backup(&backup)?;
+    println!("Restored {} files from {} to {}", restored, backup.display(), target);
+    Ok(())
+}
+
+fn show_cache() -> error::Result<()> {
+    let (count, size) = cache::usage()?;
+    println!("{} mods, {:.2}MB of {}MB in {}", count, size as f64 / 1000000.0, settings::settings().cache_limit_mb, cache::cache_dir().display());
+    Ok(())
+}
+
+fn prune_cache(limit_mb: Option<u64>) -> error::Result<()> {
+    let limit_mb = limit_mb.unwrap_or(settings::settings().cache_limit_mb);
+    let (removed, freed) = cache::evict(limit_mb)?;
+    println!("Removed {} mods from the cache, freeing {:.2}MB", removed, freed as f64 / 1000000.0);
+    Ok(())
+}
+
+// Added dependencies can have dependencies of their own, so this goes round until nothing new turns up
+async fn check_dependencies(profile: &Profile, add: bool) -> error::Result<()> {
+    let mods_dir = profile.meta.mods_dir();
+    let mut tried: Vec<String> = Vec::new();
+    loop {
+        let missing: Vec<MissingDependency> = dependencies::missing_dependencies(mods_dir.as_str())?.into_iter()
+            .filter(|dependency| !tried.contains(&dependency.id))
+            .collect();
+        if missing.is_empty() {
+            return Ok(())
+        }
+        for dependency in missing.iter() {
+            println!("Missing dependency {}, needed by {}", dependency.id, dependency.required_by.join(", "));
+        }
+        if !add {
+            println!("Run install again with --add-dependencies to download them from Modrinth");
+            return Ok(())
+        }
+        for dependency in missing {
+            tried.push(dependency.id.clone());
+            let modinfo = match dependencies::find_dependency(&dependency, &profile.meta).await? {
+                Some(modinfo) => modinfo,
+                None => {
+                    println!("{} isn't on Modrinth, it has to be installed by hand", dependency.id);
+                    continue
+                }
+            };
+            let mut download = mod_download(&modinfo);
+            download.dependency = true;
+            install_mod(&profile.meta, download, mods_dir.clone(), |_, _| {}).await?;
+            println!("Added {} {}", modinfo.name, modinfo.version);
+        }
+    }
+}
+
+async fn install(profile_query: String, add_dependencies: bool) -> error::Result<()> {
+    println!("Downloading manifest...");
+    let manifest = fetch_manifest().await?;
+    let profile = manifest.find(profile_query.as_str())
+        .ok_or(ModtoolError::Manifest(format!("No profile with id or name '{}' in the manifest", profile_query)))?;
+    println!("Using profile {} (id {})", profile.meta.name, profile.meta.id);
+    let profile = resolve::resolve_profile(profile).await?;
+
+    println!("Checking for Java...");
+    let java = match java::find_for(&profile.meta) {
+        Err(ModtoolError::JavaNotFound) | Err(ModtoolError::JavaTooOld { .. }) => {
+            let required = java::required_major(profile.meta.version.as_str());
+            println!("No Java {}+ installed, downloading Temurin {}...", required, java::runtime_major(required));
+            java::install_runtime(required).await?
+        },
+        found => found?
+    };
+    println!("Using Java {} at {}", java.version, java.path.display());
+
+    let version = match profile.meta.loader {
+        ModLoader::Forge => {
+            let (forge_version, source) = forge_version(&profile.meta).await?;
+            println!("Installing Forge {} ({}) for Minecraft {}...", forge_version, source, profile.meta.version);
+            let forge_version = install_forge(profile.meta.version.clone(), forge_version, &java).await?;
+            println!("Found Forge: {}", forge_version);
+            forge_version
+        },
+        ModLoader::Fabric => {
+            println!("Installing Fabric for Minecraft {}...", profile.meta.version);
+            let fabric_version = install_fabric(&profile.meta).await?;
+            println!("Found Fabric: {}", fabric_version);
+            fabric_version
+        },
+        ModLoader::Quilt => {
+            println!("Installing Quilt for Minecraft {}...", profile.meta.version);
+            let quilt_version = install_quilt(&profile.meta).await?;
+            println!("Found Quilt: {}", quilt_version);
+            quilt_version
+        },
+        ModLoader::NeoForge => {
+            let (neoforge_version, source) = neoforge_version(&profile.meta).await?;
+            println!("Installing NeoForge {} ({}) for Minecraft {}...", neoforge_version, source, profile.meta.version);
+            let neoforge_version = install_neoforge(neoforge_version, &java).await?;
+            println!("Found NeoForge: {}", neoforge_version);
+            neoforge_version
+        }
+    };
+
+    let mods_dir = profile.meta.mods_dir();
+    create_game_dir(&profile.meta)?;
+    let plan = mods_dir::sync_mods_dir(&profile, mods_dir.as_str())?;
+    if let Some(backup) = plan.backup.as_ref() {
+        println!("Backed up the mods folder to {}", backup.display());
+    }
+    let overrides = apply_overrides(&profile.meta)?;
+    if overrides > 0 {
+        println!("Copied {} files from the modpack overrides", overrides);
+    }
+
+    let downloads: Vec<ModDownload> = mod_downloads(&profile).into_iter()
+        .filter(|modinfo| !plan.unchanged.contains(&modinfo.url))
+        .collect();
+    let total = downloads.len();
+    let (manager, mut events) = DownloadManager::new(settings::settings().parallel_downloads);
+    for modinfo in downloads.iter() {
+        manager.enqueue(Job {
+            meta: profile.meta.clone(),
+            modinfo: modinfo.clone(),
+            mods_dir: mods_dir.clone()
+        }, 0);
+    }
+
+    // Let every download finish before giving up, so one bad mod doesn't stop the rest
+    let mut finished = 0;
+    let mut first_error = None;
+    while finished < total {
+        let (url, result) = match events.recv().await {
+            Some(DownloadEvent::Finished { url, result }) => (url, result),
+            Some(_) => continue,
+            None => break
+        };
+        finished += 1;
+        let modinfo = downloads.iter().find(|modinfo| modinfo.url == url).unwrap();
+        match result {
+            Ok(_) => println!("[{}/{}] {} {}", finished, total, modinfo.name, modinfo.version),
+            Err(err) => {
+                println!("[{}/{}] {} {} failed: {}", finished, total, modinfo.name, modinfo.version, err);
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    if let Some(err) = first_error {
+        return Err(err)
+    }
+
+    check_dependencies(&profile, add_dependencies).await?;
+
+    write_launcher_profile(&profile, version.as_str(), &java)?;
+    let summary = plan.summary;
+    println!("Installed {} for {} into {}: {} added, {} updated, {} removed, {} unchanged",
+        profile.meta.name, profile.meta.version, profile.meta.game_dir(),
+        summary.added, summary.updated, summary.removed, summary.unchanged);
+    Ok(())
+}
+
+// The release binary uses the windows subsystem, so it has no console of its own
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
@@ -0,0 +1,132 @@
+// Looks up manifest entries like `{"provider": "CurseForge", "projectID": 238222, "fileID": 3872624}`
+// in the CurseForge API. That needs an API key in settings.json. Files whose authors have turned
+// off third-party downloads are marked `manual`, with the file's page as their url.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::settings::settings;
+
+#[derive(Deserialize)]
+struct Response<T> {
+    data: T
+}
+
+#[derive(Deserialize)]
+struct File {
+    displayName: String,
+    fileName: String,
+    fileLength: i64,
+    downloadUrl: Option<String>,
+    #[serde(default)]
+    hashes: Vec<FileHash>
+}
+
+#[derive(Deserialize)]
+struct FileHash {
+    value: String,
+    // 1 is sha1, 2 is md5
+    algo: i32
+}
+
+#[derive(Deserialize)]
+struct Project {
+    links: ProjectLinks
+}
+
+#[derive(Deserialize)]
+struct ProjectLinks {
+    websiteUrl: String
+}
+
+async fn get<T: serde::de::DeserializeOwned>(path: String, not_found: String) -> Result<T> {
+    let key = match settings().curseforge_api_key {
+        Some(key) => key,
+        None => return Err(ModtoolError::Manifest(format!("CurseForge mods need an API key, set curseforge_api_key in {}", settings::Settings::path().display())))
+    };
+    let res = HTTP_CLIENT
+        .get(format!("{}{}", settings().curseforge_api.trim_end_matches('/'), path))
+        .header("x-api-key", key)
+        .header("User-Agent", format!("Starkiller645/modtool-rs/{APP_VERSION} (tallie@tallie.dev)"))
+        .send()
+        .await?;
+    match res.status() {
+        reqwest::StatusCode::NOT_FOUND => Err(ModtoolError::Manifest(not_found)),
+        reqwest::StatusCode::FORBIDDEN => Err(ModtoolError::Manifest(format!("CurseForge rejected the API key in {}", settings::Settings::path().display()))),
+        _ => Ok(res.error_for_status()?.json::<Response<T>>().await?.data)
+    }
+}
+
+// Returns a copy of `modinfo` with the url, filename, size and hash of the file
+pub async fn resolve(modinfo: &Mod) -> Result<Mod> {
+    let (project_id, file_id) = match (modinfo.projectID, modinfo.fileID) {
+        (Some(project_id), Some(file_id)) => (project_id, file_id),
+        _ => return Err(ModtoolError::Manifest(format!("{} has neither a url nor a CurseForge projectID and fileID", modinfo.name)))
+    };
+    let file: File = get(format!("/v1/mods/{}/files/{}", project_id, file_id),
+        format!("CurseForge has no file {} in project {} for {}", file_id, project_id, modinfo.name)).await?;
+
+    let mut resolved = modinfo.clone();
+    resolved.filename = Some(file.fileName);
+    resolved.size = file.fileLength as i32;
+    if resolved.version.is_empty() {
+        resolved.version = file.displayName;
+    }
+    if let Some(hash) = file.hashes.into_iter().find(|hash| hash.algo == 1) {
+        resolved.sha1 = Some(hash.value);
+    }
+    match file.downloadUrl {
+        Some(url) => resolved.url = url,
+        None => {
+            let project: Project = get(format!("/v1/mods/{}", project_id),
+                format!("CurseForge has no project {} for {}", project_id, modinfo.name)).await?;
+            resolved.url = format!("{}/files/{}", project.links.websiteUrl.trim_end_matches('/'), file_id);
+            resolved.manual = true;
+        }
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    fn entry(project_id: u64, file_id: u64) -> Mod {
+        serde_json::from_value(serde_json::json!({ "name": "Test mod", "provider": "CurseForge", "projectID": project_id, "fileID": file_id })).unwrap()
+    }
+
+    // One test, since they'd all point `curseforge_api` at their own server
+    #[tokio::test]
+    async fn resolves_against_a_local_server() {
+        test_support::home();
+        let _globals = test_support::lock_globals().await;
+        let server = MockServer::start(vec![
+            ("/v1/mods/100/files/200", 200, MockServer::json(serde_json::json!({ "data": {
+                "displayName": "Test Mod 1.0", "fileName": "test-1.0.jar", "fileLength": 2048,
+                "downloadUrl": "https://edge.example.com/test-1.0.jar",
+                "hashes": [{ "value": "md5hash", "algo": 2 }, { "value": "sha1hash", "algo": 1 }]
+            }}))),
+            ("/v1/mods/101/files/201", 200, MockServer::json(serde_json::json!({ "data": {
+                "displayName": "Locked 2.0", "fileName": "locked-2.0.jar", "fileLength": 4096, "downloadUrl": null
+            }}))),
+            ("/v1/mods/101", 200, MockServer::json(serde_json::json!({ "data": { "links": { "websiteUrl": "https://www.curseforge.com/minecraft/mc-mods/locked/" } } })))
+        ]).await;
+        {
+            let mut settings = settings::SETTINGS.write().unwrap();
+            settings.curseforge_api = server.url.clone();
+            settings.curseforge_api_key = Some(String::from("test-key"));
+        }
+
+        let resolved = resolve(&entry(100, 200)).await.unwrap();
+        assert_eq!(resolved.url, "https://edge.example.com/test-1.0.jar");
+        assert_eq!(resolved.version, "Test Mod 1.0");
+        assert_eq!(resolved.sha1.as_deref(), Some("sha1hash"));
+        assert!(!resolved.manual);
+
+        let locked = resolve(&entry(101, 201)).await.unwrap();
+        assert!(locked.manual);
+        assert_eq!(locked.url, "https://www.curseforge.com/minecraft/mc-mods/locked/files/201");
+
+        assert!(matches!(resolve(&entry(100, 999)).await, Err(ModtoolError::Manifest(message)) if message.contains("no file 999")));
+    }
+}
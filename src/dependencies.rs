@@ -0,0 +1,252 @@
+// Reads the dependency declarations inside downloaded jars (`fabric.mod.json` for Fabric, `quilt.mod.json`
+// for Quilt, `META-INF/mods.toml` for Forge, `META-INF/neoforge.mods.toml` for NeoForge) and works out
+// which required mods aren't in the mods folder.
+// Missing ones can usually be found on Modrinth under the same id.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use std::io::{Read, Seek};
+
+// Provided by the game or the loader rather than by a jar
+static BUILTIN_IDS: &[&str] = &["minecraft", "java", "fabricloader", "fabric-loader", "forge", "javafml", "neoforge", "quilt_loader"];
+
+#[derive(Deserialize)]
+struct FabricModJson {
+    id: String,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    depends: HashMap<String, serde_json::Value>
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader
+}
+
+#[derive(Deserialize)]
+struct QuiltLoader {
+    id: String,
+    // Entries are either a mod id or an object with an `id`
+    #[serde(default)]
+    provides: Vec<serde_json::Value>,
+    // The same, where an object can also be `optional`, and an array means any one of its entries will do
+    #[serde(default)]
+    depends: Vec<serde_json::Value>
+}
+
+// The id of a quilt.mod.json `provides` or `depends` entry, unless it's optional or a list of alternatives
+fn quilt_id(entry: &serde_json::Value) -> Option<String> {
+    match entry {
+        serde_json::Value::String(id) => Some(id.clone()),
+        serde_json::Value::Object(object) if !object.get("optional").and_then(|optional| optional.as_bool()).unwrap_or(false) => {
+            object.get("id").and_then(|id| id.as_str()).map(String::from)
+        },
+        _ => None
+    }
+}
+
+#[derive(Deserialize)]
+struct ModsToml {
+    #[serde(default)]
+    mods: Vec<ModsTomlMod>,
+    #[serde(default)]
+    dependencies: HashMap<String, Vec<ModsTomlDependency>>
+}
+
+#[derive(Deserialize)]
+struct ModsTomlMod {
+    modId: String
+}
+
+#[derive(Deserialize)]
+struct ModsTomlDependency {
+    modId: String,
+    #[serde(default)]
+    mandatory: bool,
+    // Newer loaders say `type = "required"` instead of `mandatory = true`
+    #[serde(default, rename = "type")]
+    kind: Option<String>
+}
+
+#[derive(Clone, PartialEq)]
+pub struct MissingDependency {
+    pub id: String,
+    // Names of the jars that need it
+    pub required_by: Vec<String>
+}
+
+#[derive(Default)]
+struct JarMetadata {
+    provides: Vec<String>,
+    depends: Vec<String>
+}
+
+// Collects the mod ids a jar provides and requires, including those of jars bundled inside it
+fn read_jar<R: Read + Seek>(reader: R, metadata: &mut JarMetadata) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+
+    if let Ok(mut entry) = archive.by_name("fabric.mod.json") {
+        let mut data = String::new();
+        entry.read_to_string(&mut data)?;
+        // Some mods ship slightly broken JSON, those are skipped rather than failing the check
+        if let Ok(info) = serde_json::from_str::<FabricModJson>(data.as_str()) {
+            metadata.provides.push(info.id);
+            metadata.provides.extend(info.provides);
+            metadata.depends.extend(info.depends.into_keys());
+        }
+    }
+
+    if let Ok(mut entry) = archive.by_name("quilt.mod.json") {
+        let mut data = String::new();
+        entry.read_to_string(&mut data)?;
+        if let Ok(info) = serde_json::from_str::<QuiltModJson>(data.as_str()) {
+            metadata.provides.push(info.quilt_loader.id);
+            metadata.provides.extend(info.quilt_loader.provides.iter().filter_map(quilt_id));
+            metadata.depends.extend(info.quilt_loader.depends.iter().filter_map(quilt_id));
+        }
+    }
+
+    for toml_name in ["META-INF/mods.toml", "META-INF/neoforge.mods.toml"] {
+        let mut entry = match archive.by_name(toml_name) {
+            Ok(entry) => entry,
+            Err(_) => continue
+        };
+        let mut data = String::new();
+        entry.read_to_string(&mut data)?;
+        if let Ok(info) = toml::from_str::<ModsToml>(data.as_str()) {
+            metadata.provides.extend(info.mods.into_iter().map(|info| info.modId));
+            for dependency in info.dependencies.into_values().flatten() {
+                if dependency.mandatory || dependency.kind.as_deref() == Some("required") {
+                    metadata.depends.push(dependency.modId);
+                }
+            }
+        }
+    }
+
+    // Jar-in-jar, `META-INF/jars` for Fabric and `META-INF/jarjar` for Forge
+    let nested: Vec<String> = archive.file_names()
+        .filter(|name| (name.starts_with("META-INF/jars/") || name.starts_with("META-INF/jarjar/")) && name.ends_with(".jar"))
+        .map(String::from)
+        .collect();
+    for name in nested {
+        let mut data = Vec::new();
+        // A broken bundled jar shouldn't hide what the rest of the jar needs
+        let res = archive.by_name(name.as_str())
+            .map_err(ModtoolError::from)
+            .and_then(|mut entry| entry.read_to_end(&mut data).map_err(ModtoolError::from))
+            .and_then(|_| read_jar(Cursor::new(data), metadata));
+        if let Err(err) = res {
+            log::warn!("Could not read the mod info in bundled jar {}: {}", name, err);
+        }
+    }
+    Ok(())
+}
+
+// Checks every jar in `mods_dir`, so mods the user added themselves count too
+pub fn missing_dependencies(mods_dir: &str) -> Result<Vec<MissingDependency>> {
+    let mut provided: Vec<String> = BUILTIN_IDS.iter().map(|id| String::from(*id)).collect();
+    let mut required: Vec<(String, String)> = Vec::new();
+
+    if !Path::new(mods_dir).exists() {
+        return Ok(Vec::new())
+    }
+    for entry in std::fs::read_dir(mods_dir).map_err(|err| ModtoolError::io(mods_dir, err))? {
+        let path = entry.map_err(|err| ModtoolError::io(mods_dir, err))?.path();
+        if path.extension().map(|ext| ext != "jar").unwrap_or(true) {
+            continue
+        }
+        let fhandle = File::open(&path).map_err(|err| ModtoolError::io(&path, err))?;
+        let mut metadata = JarMetadata::default();
+        if let Err(err) = read_jar(fhandle, &mut metadata) {
+            log::warn!("Could not read the mod info in {}: {}", path.display(), err);
+            continue
+        }
+        let jar = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        provided.extend(metadata.provides);
+        required.extend(metadata.depends.into_iter().map(|id| (id, jar.clone())));
+    }
+
+    let mut missing: Vec<MissingDependency> = Vec::new();
+    for (id, jar) in required {
+        if provided.contains(&id) {
+            continue
+        }
+        match missing.iter_mut().find(|dependency| dependency.id == id) {
+            Some(dependency) => {
+                if !dependency.required_by.contains(&jar) {
+                    dependency.required_by.push(jar);
+                }
+            },
+            None => missing.push(MissingDependency { id, required_by: vec![jar] })
+        }
+    }
+    Ok(missing)
+}
+
+// Looks for a missing dependency on Modrinth, using its mod id as the project slug.
+// Returns None if there's no compatible project with that slug.
+pub async fn find_dependency(dependency: &MissingDependency, meta: &ProfileMeta) -> Result<Option<Mod>> {
+    let candidate = Mod {
+        name: dependency.id.clone(),
+        url: String::new(),
+        version: String::new(),
+        provider: ModProvider::Modrinth,
+        size: 0,
+        project: Some(dependency.id.clone()),
+        projectID: None,
+        fileID: None,
+        filename: None,
+        manual: false,
+        sha1: None,
+        sha256: None,
+        sha512: None
+    };
+    match modrinth::resolve(&candidate, meta).await {
+        Ok(resolved) => Ok(Some(resolved)),
+        Err(ModtoolError::Manifest(_)) => Ok(None),
+        Err(err) => Err(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zip::write::FileOptions;
+
+    fn jar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_quilt_mod_json() {
+        let quilt = br#"{ "schema_version": 1, "quilt_loader": {
+            "id": "example", "provides": ["example_api", { "id": "example_extra" }],
+            "depends": ["quilt_loader", { "id": "qsl" }, { "id": "modmenu", "optional": true }, [{ "id": "a" }, { "id": "b" }]]
+        } }"#;
+        let mut metadata = JarMetadata::default();
+        read_jar(Cursor::new(jar(&[("quilt.mod.json", quilt)])), &mut metadata).unwrap();
+        assert_eq!(metadata.provides, vec!["example", "example_api", "example_extra"]);
+        assert_eq!(metadata.depends, vec!["quilt_loader", "qsl"]);
+    }
+
+    #[test]
+    fn broken_nested_jar_keeps_the_outer_dependencies() {
+        let fabric = br#"{ "id": "outer", "depends": { "fabric-api": "*" } }"#;
+        let inner = jar(&[("fabric.mod.json", br#"{ "id": "inner" }"#)]);
+        let outer = jar(&[
+            ("fabric.mod.json", fabric),
+            ("META-INF/jars/broken.jar", b"not a zip"),
+            ("META-INF/jars/inner.jar", &inner)
+        ]);
+        let mut metadata = JarMetadata::default();
+        read_jar(Cursor::new(outer), &mut metadata).unwrap();
+        assert_eq!(metadata.provides, vec!["outer", "inner"]);
+        assert_eq!(metadata.depends, vec!["fabric-api"]);
+    }
+}
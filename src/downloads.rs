@@ -0,0 +1,334 @@
+// Download manager shared by the GUI and the headless installer. Jobs wait in a priority queue
+// and at most `limit` of them run at once on the tokio runtime. Whoever owns the manager gets
+// its progress as `DownloadEvent`s, so nothing in here knows about Dioxus.
+//
+// The queue itself is `Scheduler`, which does no IO and can be driven by hand.
+
+use crate::*;
+use crate::error::Result;
+use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+#[derive(PartialEq, Eq)]
+struct QueuedJob {
+    priority: i32,
+    // Breaks ties between equal priorities, lower was queued first
+    seq: u64,
+    url: String
+}
+
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &QueuedJob) -> Ordering {
+        self.priority.cmp(&other.priority).then(other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &QueuedJob) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Decides which download runs next. Higher priorities go first, then the order they were queued in.
+pub struct Scheduler {
+    limit: usize,
+    paused: bool,
+    queue: BinaryHeap<QueuedJob>,
+    running: Vec<QueuedJob>,
+    next_seq: u64
+}
+
+impl Scheduler {
+    pub fn new(limit: usize) -> Scheduler {
+        Scheduler {
+            limit: limit.max(1),
+            paused: false,
+            queue: BinaryHeap::new(),
+            running: Vec::new(),
+            next_seq: 0
+        }
+    }
+
+    pub fn push(&mut self, url: String, priority: i32) {
+        self.remove(url.as_str());
+        self.queue.push(QueuedJob { priority, seq: self.next_seq, url });
+        self.next_seq += 1;
+    }
+
+    // The next download to start, if there's a free worker and the queue isn't paused
+    pub fn next(&mut self) -> Option<String> {
+        if self.paused || self.running.len() >= self.limit {
+            return None
+        }
+        let job = self.queue.pop()?;
+        let url = job.url.clone();
+        self.running.push(job);
+        Some(url)
+    }
+
+    pub fn finish(&mut self, url: &str) {
+        self.running.retain(|job| job.url != url);
+    }
+
+    // Takes a download out of the queue. Returns whether it was waiting there.
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|job| job.url != url);
+        self.queue.len() != before
+    }
+
+    pub fn is_running(&self, url: &str) -> bool {
+        self.running.iter().any(|job| job.url == url)
+    }
+
+    // Stops new downloads from starting, and puts the running ones back in the queue ahead of
+    // anything queued after them. Returns the downloads that were interrupted.
+    pub fn pause(&mut self) -> Vec<String> {
+        self.paused = true;
+        let mut interrupted = Vec::new();
+        for job in self.running.drain(..) {
+            interrupted.push(job.url.clone());
+            self.queue.push(job);
+        }
+        interrupted
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Job {
+    pub meta: ProfileMeta,
+    pub modinfo: ModDownload,
+    pub mods_dir: String
+}
+
+pub enum DownloadEvent {
+    Queued { url: String },
+    Started { url: String },
+    Progress { url: String, bytes_read: u64, bytes_total: u64 },
+    Finished { url: String, result: Result<()> },
+    Cancelled { url: String }
+}
+
+struct Inner {
+    scheduler: Scheduler,
+    jobs: HashMap<String, Job>,
+    tasks: HashMap<String, JoinHandle<()>>
+}
+
+// Cheap to clone, every clone drives the same queue
+#[derive(Clone)]
+pub struct DownloadManager {
+    inner: Arc<Mutex<Inner>>,
+    events: UnboundedSender<DownloadEvent>
+}
+
+impl PartialEq for DownloadManager {
+    fn eq(&self, other: &DownloadManager) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl DownloadManager {
+    pub fn new(limit: usize) -> (DownloadManager, UnboundedReceiver<DownloadEvent>) {
+        let (events, receiver) = unbounded_channel();
+        let manager = DownloadManager {
+            inner: Arc::new(Mutex::new(Inner {
+                scheduler: Scheduler::new(limit),
+                jobs: HashMap::new(),
+                tasks: HashMap::new()
+            })),
+            events
+        };
+        (manager, receiver)
+    }
+
+    // Must be called from inside a tokio runtime, since that's where the downloads run
+    pub fn enqueue(&self, job: Job, priority: i32) {
+        let url = job.modinfo.url.clone();
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.scheduler.is_running(url.as_str()) {
+                return
+            }
+            inner.jobs.insert(url.clone(), job);
+            inner.scheduler.push(url.clone(), priority);
+        }
+        self.events.send(DownloadEvent::Queued { url }).ok();
+        self.pump();
+    }
+
+    pub fn cancel(&self, url: &str) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.scheduler.remove(url) && !inner.scheduler.is_running(url) {
+                return
+            }
+            inner.scheduler.finish(url);
+            inner.jobs.remove(url);
+            if let Some(task) = inner.tasks.remove(url) {
+                task.abort();
+            }
+        }
+        self.events.send(DownloadEvent::Cancelled { url: String::from(url) }).ok();
+        self.pump();
+    }
+
+    // Running downloads are stopped and go back in the queue. They pick up from their
+    // `.part` file when the queue is resumed.
+    pub fn pause(&self) {
+        let interrupted = {
+            let mut inner = self.inner.lock().unwrap();
+            let interrupted = inner.scheduler.pause();
+            for url in interrupted.iter() {
+                if let Some(task) = inner.tasks.remove(url) {
+                    task.abort();
+                }
+            }
+            interrupted
+        };
+        for url in interrupted {
+            self.events.send(DownloadEvent::Queued { url }).ok();
+        }
+    }
+
+    pub fn resume(&self) {
+        self.inner.lock().unwrap().scheduler.resume();
+        self.pump();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.lock().unwrap().scheduler.is_paused()
+    }
+
+    // Starts queued downloads until every worker is busy
+    fn pump(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        while let Some(url) = inner.scheduler.next() {
+            let job = match inner.jobs.get(&url) {
+                Some(job) => job.clone(),
+                None => {
+                    inner.scheduler.finish(url.as_str());
+                    continue
+                }
+            };
+            let task = tokio::spawn(self.clone().run(url.clone(), job));
+            inner.tasks.insert(url, task);
+        }
+    }
+
+    async fn run(self, url: String, job: Job) {
+        self.events.send(DownloadEvent::Started { url: url.clone() }).ok();
+
+        // Only report whole percentage steps, the UI re-renders for every event
+        let events = self.events.clone();
+        let progress_url = url.clone();
+        let mut last_step = u64::MAX;
+        let res = install::install_mod(&job.meta, job.modinfo, job.mods_dir, move |bytes_read, bytes_total| {
+            let step = match bytes_total {
+                0 => bytes_read / 262144,
+                _ => bytes_read * 100 / bytes_total
+            };
+            if step != last_step {
+                last_step = step;
+                events.send(DownloadEvent::Progress { url: progress_url.clone(), bytes_read, bytes_total }).ok();
+            }
+        }).await;
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.scheduler.finish(url.as_str());
+            inner.jobs.remove(&url);
+            inner.tasks.remove(&url);
+        }
+        self.events.send(DownloadEvent::Finished { url, result: res }).ok();
+        self.pump();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(scheduler: &mut Scheduler) -> Vec<String> {
+        std::iter::from_fn(|| scheduler.next()).collect()
+    }
+
+    #[test]
+    fn higher_priority_first_then_queue_order() {
+        let mut scheduler = Scheduler::new(10);
+        scheduler.push(String::from("a"), 0);
+        scheduler.push(String::from("b"), 5);
+        scheduler.push(String::from("c"), 0);
+        scheduler.push(String::from("d"), 5);
+        assert_eq!(started(&mut scheduler), vec!["b", "d", "a", "c"]);
+    }
+
+    #[test]
+    fn never_runs_more_than_the_limit() {
+        let mut scheduler = Scheduler::new(2);
+        for url in ["a", "b", "c", "d"] {
+            scheduler.push(String::from(url), 0);
+        }
+        assert_eq!(started(&mut scheduler), vec!["a", "b"]);
+        scheduler.finish("a");
+        assert_eq!(started(&mut scheduler), vec!["c"]);
+        scheduler.finish("b");
+        scheduler.finish("c");
+        assert_eq!(started(&mut scheduler), vec!["d"]);
+    }
+
+    #[test]
+    fn pause_requeues_running_ahead_of_later_jobs() {
+        let mut scheduler = Scheduler::new(1);
+        scheduler.push(String::from("a"), 0);
+        scheduler.push(String::from("b"), 0);
+        assert_eq!(started(&mut scheduler), vec!["a"]);
+
+        assert_eq!(scheduler.pause(), vec!["a"]);
+        assert!(scheduler.is_paused());
+        assert!(!scheduler.is_running("a"));
+        assert_eq!(scheduler.next(), None);
+
+        scheduler.resume();
+        assert_eq!(started(&mut scheduler), vec!["a"]);
+        scheduler.finish("a");
+        assert_eq!(started(&mut scheduler), vec!["b"]);
+    }
+
+    #[test]
+    fn remove_takes_queued_jobs_but_not_running_ones() {
+        let mut scheduler = Scheduler::new(1);
+        scheduler.push(String::from("a"), 0);
+        scheduler.push(String::from("b"), 0);
+        scheduler.push(String::from("c"), 0);
+        assert_eq!(started(&mut scheduler), vec!["a"]);
+
+        assert!(scheduler.remove("b"));
+        assert!(!scheduler.remove("b"));
+        // Running jobs are stopped with `finish`, `remove` only looks at the queue
+        assert!(!scheduler.remove("a"));
+        assert!(scheduler.is_running("a"));
+        scheduler.finish("a");
+        assert_eq!(started(&mut scheduler), vec!["c"]);
+    }
+
+    #[test]
+    fn pushing_again_replaces_the_queued_job() {
+        let mut scheduler = Scheduler::new(10);
+        scheduler.push(String::from("a"), 0);
+        scheduler.push(String::from("b"), 0);
+        scheduler.push(String::from("a"), 1);
+        assert_eq!(started(&mut scheduler), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,121 @@
+// Crate-wide error type. Every install step returns `Result<T>`, and the GUI turns
+// failures into an `AppError` for `Page::Error`.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ModtoolError {
+    Network(reqwest::Error),
+    Timeout { url: String },
+    Io { path: String, err: std::io::Error },
+    Json(serde_json::Error),
+    Archive(zip::result::ZipError),
+    Manifest(String),
+    JavaNotFound,
+    JavaTooOld { mc_version: String, required: u32, newest: u32 },
+    RuntimeDownload { major: u32, message: String },
+    LoaderInstall { loader: String, message: String },
+    HashMismatch { name: String, algorithm: &'static str, expected: String, actual: String },
+    ManualDownload { name: String, filename: String, page: String, downloads_dir: String },
+    NoHomeDir { var: &'static str }
+}
+
+pub type Result<T> = std::result::Result<T, ModtoolError>;
+
+impl ModtoolError {
+    pub fn io(path: impl AsRef<std::path::Path>, err: std::io::Error) -> ModtoolError {
+        ModtoolError::Io { path: path.as_ref().display().to_string(), err }
+    }
+
+    pub fn loader(loader: &str, message: String) -> ModtoolError {
+        ModtoolError::LoaderInstall { loader: String::from(loader), message }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ModtoolError::Network(_) => "Network error",
+            ModtoolError::Timeout { .. } => "Network timeout",
+            ModtoolError::Io { .. } => "File error",
+            ModtoolError::Json(_) => "Invalid data",
+            ModtoolError::Archive(_) => "Archive error",
+            ModtoolError::Manifest(_) => "Manifest error",
+            ModtoolError::JavaNotFound => "Java not found",
+            ModtoolError::JavaTooOld { .. } => "Java too old",
+            ModtoolError::RuntimeDownload { .. } => "Java download failed",
+            ModtoolError::LoaderInstall { .. } => "Mod loader install failed",
+            ModtoolError::HashMismatch { .. } => "Download failed verification",
+            ModtoolError::ManualDownload { .. } => "Manual download needed",
+            ModtoolError::NoHomeDir { .. } => "Home folder not found"
+        }
+    }
+
+    // Where the user can get things manually if we can't do it for them
+    pub fn help_url(&self) -> Option<&'static str> {
+        match self {
+            ModtoolError::LoaderInstall { loader, .. } => match loader.as_str() {
+                "Forge" => Some("https://files.minecraftforge.net/"),
+                "Fabric" => Some("https://fabricmc.net/use/installer/"),
+                "Quilt" => Some("https://quiltmc.org/en/install/"),
+                "NeoForge" => Some("https://neoforged.net/"),
+                _ => None
+            },
+            ModtoolError::JavaNotFound | ModtoolError::JavaTooOld { .. } | ModtoolError::RuntimeDownload { .. } => Some("https://adoptium.net/"),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ModtoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModtoolError::Network(err) => write!(f, "{}", err),
+            ModtoolError::Timeout { url } => write!(f, "{} stopped sending data", url),
+            ModtoolError::Io { path, err } => match path.is_empty() {
+                true => write!(f, "{}", err),
+                false => write!(f, "{}: {}", path, err)
+            },
+            ModtoolError::Json(err) => write!(f, "{}", err),
+            ModtoolError::Archive(err) => write!(f, "{}", err),
+            ModtoolError::Manifest(message) => write!(f, "{}", message),
+            ModtoolError::JavaNotFound => write!(f, "Could not find a Java installation"),
+            ModtoolError::JavaTooOld { mc_version, required, newest } => {
+                write!(f, "Minecraft {} needs Java {} or newer, but the newest Java installed is {}", mc_version, required, newest)
+            },
+            ModtoolError::RuntimeDownload { major, message } => write!(f, "Could not download Java {}: {}", major, message),
+            ModtoolError::LoaderInstall { loader, message } => write!(f, "Could not install {}: {}", loader, message),
+            ModtoolError::HashMismatch { name, algorithm, expected, actual } => {
+                write!(f, "{} does not match its {} hash (expected {}, got {})", name, algorithm, expected, actual)
+            },
+            ModtoolError::ManualDownload { name, filename, page, downloads_dir } => {
+                write!(f, "{} can't be downloaded automatically. Download {} from {} into {} and try again", name, filename, page, downloads_dir)
+            },
+            ModtoolError::NoHomeDir { var } => write!(f, "{} isn't set, so the Downloads folder can't be found", var)
+        }
+    }
+}
+
+impl std::error::Error for ModtoolError {}
+
+impl From<reqwest::Error> for ModtoolError {
+    fn from(err: reqwest::Error) -> ModtoolError {
+        ModtoolError::Network(err)
+    }
+}
+
+impl From<std::io::Error> for ModtoolError {
+    fn from(err: std::io::Error) -> ModtoolError {
+        ModtoolError::Io { path: String::new(), err }
+    }
+}
+
+impl From<serde_json::Error> for ModtoolError {
+    fn from(err: serde_json::Error) -> ModtoolError {
+        ModtoolError::Json(err)
+    }
+}
+
+impl From<zip::result::ZipError> for ModtoolError {
+    fn from(err: zip::result::ZipError) -> ModtoolError {
+        ModtoolError::Archive(err)
+    }
+}
@@ -0,0 +1,113 @@
+// Checks downloads against the sha1/sha256/sha512 hashes given in the manifest
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::io::Read;
+use std::path::Path;
+
+pub fn file_sha1(path: &Path) -> std::io::Result<String> {
+    file_digest::<Sha1>(path)
+}
+
+pub fn file_sha512(path: &Path) -> std::io::Result<String> {
+    file_digest::<Sha512>(path)
+}
+
+fn file_digest<D: Digest>(path: &Path) -> std::io::Result<String> where sha2::digest::Output<D>: std::fmt::LowerHex {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = D::new();
+    let mut buf = [0u8; 65536];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Zip archives store a CRC32 for every file, so this is enough to compare a file against one
+pub fn file_crc32(path: &Path) -> std::io::Result<u32> {
+    let mut file = std::fs::File::open(path)?;
+    let mut crc = flate2::Crc::new();
+    let mut buf = [0u8; 65536];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break
+        }
+        crc.update(&buf[..read]);
+    }
+    Ok(crc.sum())
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+pub struct Mismatch {
+    pub algorithm: &'static str,
+    pub expected: String,
+    pub actual: String
+}
+
+// Incrementally hashes a download with every algorithm the manifest has a hash for
+pub struct Verifier {
+    sha1: Option<(Sha1, String)>,
+    sha256: Option<(Sha256, String)>,
+    sha512: Option<(Sha512, String)>
+}
+
+impl Verifier {
+    pub fn new(sha1: &Option<String>, sha256: &Option<String>, sha512: &Option<String>) -> Verifier {
+        Verifier {
+            sha1: sha1.clone().map(|hash| (Sha1::new(), hash.to_lowercase())),
+            sha256: sha256.clone().map(|hash| (Sha256::new(), hash.to_lowercase())),
+            sha512: sha512.clone().map(|hash| (Sha512::new(), hash.to_lowercase()))
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        if let Some((hasher, _)) = self.sha1.as_mut() {
+            hasher.update(chunk);
+        }
+        if let Some((hasher, _)) = self.sha256.as_mut() {
+            hasher.update(chunk);
+        }
+        if let Some((hasher, _)) = self.sha512.as_mut() {
+            hasher.update(chunk);
+        }
+    }
+
+    pub fn update_from_file(&mut self, path: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::open(path)?;
+        let mut buf = [0u8; 65536];
+        loop {
+            let read = file.read(&mut buf)?;
+            if read == 0 {
+                return Ok(())
+            }
+            self.update(&buf[..read]);
+        }
+    }
+
+    pub fn verify(self) -> Result<(), Mismatch> {
+        let mut results = Vec::new();
+        if let Some((hasher, expected)) = self.sha1 {
+            results.push(("sha1", expected, format!("{:x}", hasher.finalize())));
+        }
+        if let Some((hasher, expected)) = self.sha256 {
+            results.push(("sha256", expected, format!("{:x}", hasher.finalize())));
+        }
+        if let Some((hasher, expected)) = self.sha512 {
+            results.push(("sha512", expected, format!("{:x}", hasher.finalize())));
+        }
+        for (algorithm, expected, actual) in results {
+            if expected != actual {
+                return Err(Mismatch { algorithm, expected, actual })
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,795 @@
+// Install steps shared by the GUI pages and the headless `install` command.
+// None of these touch Dioxus state, so callers decide how to report progress.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::hashes::Verifier;
+use std::path::PathBuf;
+
+// Names of everything in `.minecraft/versions`
+pub fn installed_versions() -> Result<Vec<String>> {
+    let profiles_dir = MC_DATA.profiles_dir.clone();
+    let mut versions = Vec::new();
+    for version in std::fs::read_dir(&profiles_dir).map_err(|err| ModtoolError::io(&profiles_dir, err))? {
+        let version = version.map_err(|err| ModtoolError::io(&profiles_dir, err))?;
+        versions.push(version.file_name().to_string_lossy().to_string());
+    }
+    Ok(versions)
+}
+
+// The name a mod is saved under in the mods folder
+pub fn mod_filename(filename: &Option<String>, url: &str) -> Option<String> {
+    if let Some(filename) = filename.as_ref() {
+        return Path::new(filename).file_name().map(|name| name.to_string_lossy().to_string())
+    }
+    match url.rsplit('/').next() {
+        Some(filename) if !filename.is_empty() => Some(filename.to_string()),
+        _ => None
+    }
+}
+
+// Where manual downloads are picked up from
+pub fn downloads_dir() -> Result<String> {
+    let (var, folder) = match cfg!(windows) {
+        true => ("USERPROFILE", "\\Downloads"),
+        false => ("HOME", "/Downloads")
+    };
+    env::var(var).map(|home| home + folder).map_err(|_| ModtoolError::NoHomeDir { var })
+}
+
+async fn download_installer(installer_url: String) -> Result<String> {
+    let res = HTTP_CLIENT
+        .get(installer_url.clone())
+        .header("User-Agent", format!("Starkiller645/modtool_rs/{APP_VERSION} (tallie@tallie.dev)"))
+        .timeout(FILE_DOWNLOAD_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let filename = installer_url.rsplit('/').next().unwrap_or("installer.jar");
+    let filepath = format!("{}{}", CACHE_DIR.as_str(), filename);
+    let mut fhandle = File::create(&filepath).map_err(|err| ModtoolError::io(&filepath, err))?;
+    let mut content = Cursor::new(res.bytes().await?);
+    std::io::copy(&mut content, &mut fhandle).map_err(|err| ModtoolError::io(&filepath, err))?;
+    Ok(filepath)
+}
+
+fn run_java(loader: &str, com: &Path, args: &[&str]) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    let output = process::Command::new(com)
+        .args(args)
+        .creation_flags(0x08000000)
+        .output();
+    #[cfg(not(target_os = "windows"))]
+    let output = process::Command::new(com)
+        .args(args)
+        .output();
+    match output {
+        Ok(_) => Ok(()),
+        Err(err) => Err(ModtoolError::loader(loader, format!("could not run the installer: {}", err)))
+    }
+}
+
+pub fn installer_log_dir() -> PathBuf {
+    Path::new(CACHE_DIR.as_str()).join("logs")
+}
+
+// Whether the interactive installer has somewhere to show its window
+fn has_display() -> bool {
+    cfg!(windows) || cfg!(target_os = "macos") || env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+// Runs a Forge or NeoForge installer with `--installClient`, which needs no window, on the Java picked
+// for the profile. Everything it prints goes to a log in CACHE_DIR/logs, and failures point at that log.
+// Installers too old for `--installClient` fall back to the interactive window when there's a display.
+async fn run_installer(loader: &str, java: &java::JavaInstall, installer: &str, version_name: &str) -> Result<()> {
+    let log_dir = installer_log_dir();
+    std::fs::create_dir_all(&log_dir).map_err(|err| ModtoolError::io(&log_dir, err))?;
+    let log_path = log_dir.join(format!("{}-{}.log", version_name, Utc::now().format("%Y-%m-%d_%H-%M-%S")));
+
+    let java = java.path.as_path();
+    let args = ["-jar", installer, "--installClient", MC_DATA.base_dir.as_str()];
+    let mut command = tokio::process::Command::new(java);
+    command.args(args).stdin(process::Stdio::null());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let output = command.output().await
+        .map_err(|err| ModtoolError::loader(loader, format!("could not run the installer: {}", err)))?;
+
+    let log = format!("> {} {}\n{}\n\n--- stdout ---\n{}\n--- stderr ---\n{}",
+        java.display(), args.join(" "), output.status,
+        String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    std::fs::write(&log_path, &log).map_err(|err| ModtoolError::io(&log_path, err))?;
+
+    if output.status.success() {
+        return Ok(())
+    }
+    if log.contains("installClient is not a recognized option") {
+        if has_display() {
+            return run_java(loader, java, &["-jar", installer])
+        }
+        return Err(ModtoolError::loader(loader, format!("this installer can't run without a window, and there's no display. See {}", log_path.display())))
+    }
+    let status = match output.status.code() {
+        Some(code) => format!("exit code {}", code),
+        None => String::from("it was stopped")
+    };
+    Err(ModtoolError::loader(loader, format!("the installer failed ({}), see {}", status, log_path.display())))
+}
+
+#[derive(Deserialize)]
+struct ForgePromotions {
+    // Keyed by e.g. `1.18.2-recommended` and `1.18.2-latest`
+    promos: HashMap<String, String>
+}
+
+async fn modtool_forge_versions() -> Result<Vec<ForgeVersion>> {
+    Ok(HTTP_CLIENT
+        .get("https://tallie.dev/modtool/forge_versions.json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+// Picks the Forge build for a profile: the one it pins, else ours from forge_versions.json, else
+// Forge's recommended (or latest) build. Returns the version and where it came from.
+pub async fn forge_version(meta: &ProfileMeta) -> Result<(String, &'static str)> {
+    if let Some(version) = meta.loader_version.as_ref() {
+        return Ok((version.clone(), "pinned by the profile"))
+    }
+
+    // Forge's own promotions still work when the ModTool list can't be had
+    match modtool_forge_versions().await {
+        Ok(forge_manifest) => {
+            if let Some(version) = forge_manifest.into_iter().rev().find(|version| version.minecraft == meta.version) {
+                return Ok((version.forge, "from the ModTool version list"))
+            }
+        },
+        Err(err) => log::warn!("Could not read the ModTool Forge version list, using Forge's promotions instead: {}", err)
+    }
+
+    let promotions: ForgePromotions = HTTP_CLIENT
+        .get("https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(version) = promotions.promos.get(&format!("{}-recommended", meta.version)) {
+        return Ok((version.clone(), "Forge's recommended build"))
+    }
+    if let Some(version) = promotions.promos.get(&format!("{}-latest", meta.version)) {
+        return Ok((version.clone(), "Forge's latest build"))
+    }
+    Err(ModtoolError::loader("Forge", format!("no Forge version is known for Minecraft {}", meta.version)))
+}
+
+// Returns the installed version name, e.g. `1.18.2-forge-40.1.0`
+pub async fn install_forge(mc_version: String, forge_version: String, java: &java::JavaInstall) -> Result<String> {
+    let version_name = format!("{}-forge-{}", mc_version, forge_version);
+    if installed_versions()?.contains(&version_name) {
+        return Ok(version_name)
+    }
+
+    let installer_url = format!("https://maven.minecraftforge.net/net/minecraftforge/forge/{0}-{1}/forge-{0}-{1}-installer.jar", mc_version, forge_version);
+    let filepath = download_installer(installer_url).await?;
+
+    run_installer("Forge", java, filepath.as_str(), version_name.as_str()).await?;
+
+    if installed_versions()?.contains(&version_name) {
+        Ok(version_name)
+    } else {
+        Err(ModtoolError::loader("Forge", format!("the installer finished without adding {}, see the log in {}", version_name, installer_log_dir().display())))
+    }
+}
+
+#[derive(Deserialize)]
+struct MetaLoaderEntry {
+    loader: MetaLoader
+}
+
+#[derive(Deserialize)]
+struct MetaLoader {
+    version: String,
+    // Fabric marks stable builds, Quilt only has `-beta` in the version of the others
+    #[serde(default)]
+    stable: Option<bool>
+}
+
+async fn meta_get(api: &str, path: String) -> Result<reqwest::Response> {
+    let res = HTTP_CLIENT
+        .get(format!("{}{}", api.trim_end_matches('/'), path))
+        .header("User-Agent", format!("Starkiller645/modtool_rs/{APP_VERSION} (tallie@tallie.dev)"))
+        .send()
+        .await?;
+    Ok(res)
+}
+
+// Fabric and Quilt publish a launcher version JSON for every loader build, so installing one is just
+// writing that to `versions/`, no installer needed. `name` is `fabric` or `quilt`, as in `fabric-loader`.
+// Uses the profile's pinned loader version, or else the newest stable one for its Minecraft version.
+// Returns the installed version name, e.g. `fabric-loader-0.14.8-1.19`
+async fn install_meta_loader(loader: &str, api: String, name: &str, meta: &ProfileMeta) -> Result<String> {
+    let loader_version = match meta.loader_version.as_ref() {
+        Some(version) => version.clone(),
+        None => {
+            let loaders: Vec<MetaLoaderEntry> = meta_get(api.as_str(), format!("/versions/loader/{}", meta.version)).await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let newest = loaders.iter()
+                .find(|entry| entry.loader.stable.unwrap_or(!entry.loader.version.contains('-')))
+                .or(loaders.first());
+            match newest {
+                Some(entry) => entry.loader.version.clone(),
+                None => return Err(ModtoolError::loader(loader, format!("{} doesn't support Minecraft {} yet", loader, meta.version)))
+            }
+        }
+    };
+    let version_name = format!("{}-loader-{}-{}", name, loader_version, meta.version);
+    let version_json = Path::new(MC_DATA.profiles_dir.as_str()).join(&version_name).join(format!("{}.json", version_name));
+    if version_json.exists() {
+        return Ok(version_name)
+    }
+
+    let res = meta_get(api.as_str(), format!("/versions/loader/{}/{}/profile/json", meta.version, loader_version)).await?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND || res.status() == reqwest::StatusCode::BAD_REQUEST {
+        return Err(ModtoolError::loader(loader, format!("there is no loader {} for Minecraft {}", loader_version, meta.version)))
+    }
+    let profile: serde_json::Value = res.error_for_status()?.json().await?;
+    if profile["id"].as_str() != Some(version_name.as_str()) {
+        return Err(ModtoolError::loader(loader, format!("got a profile for {} instead of {}", profile["id"], version_name)))
+    }
+
+    let dir = version_json.parent().unwrap();
+    std::fs::create_dir_all(dir).map_err(|err| ModtoolError::io(dir, err))?;
+    std::fs::write(&version_json, serde_json::to_string_pretty(&profile)?).map_err(|err| ModtoolError::io(&version_json, err))?;
+    Ok(version_name)
+}
+
+pub async fn install_fabric(meta: &ProfileMeta) -> Result<String> {
+    install_meta_loader("Fabric", settings::settings().fabric_meta, "fabric", meta).await
+}
+
+pub async fn install_quilt(meta: &ProfileMeta) -> Result<String> {
+    install_meta_loader("Quilt", settings::settings().quilt_meta, "quilt", meta).await
+}
+
+#[derive(Deserialize)]
+struct MavenVersions {
+    // Oldest first
+    versions: Vec<String>
+}
+
+// NeoForge versions drop the `1.` from Minecraft's, so 1.20.4 gets 20.4.x and 1.21 gets 21.0.x
+fn neoforge_prefix(mc_version: &str) -> Option<String> {
+    let mut parts = mc_version.strip_prefix("1.")?.split('.');
+    let major = parts.next()?;
+    let minor = parts.next().unwrap_or("0");
+    Some(format!("{}.{}.", major, minor))
+}
+
+// Picks the NeoForge build for a profile: the one it pins, else the newest stable (or beta) one.
+// Returns the version and where it came from.
+pub async fn neoforge_version(meta: &ProfileMeta) -> Result<(String, &'static str)> {
+    if let Some(version) = meta.loader_version.as_ref() {
+        return Ok((version.clone(), "pinned by the profile"))
+    }
+    let prefix = neoforge_prefix(meta.version.as_str())
+        .ok_or(ModtoolError::loader("NeoForge", format!("{} isn't a Minecraft version NeoForge knows", meta.version)))?;
+    let maven: MavenVersions = HTTP_CLIENT
+        .get(format!("{}/api/maven/versions/releases/net/neoforged/neoforge", settings::settings().neoforge_maven))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let builds: Vec<&String> = maven.versions.iter().filter(|version| version.starts_with(prefix.as_str())).collect();
+    if let Some(version) = builds.iter().rev().find(|version| !version.contains('-')) {
+        return Ok((version.to_string(), "NeoForge's latest stable build"))
+    }
+    match builds.last() {
+        Some(version) => Ok((version.to_string(), "NeoForge's latest beta")),
+        None => Err(ModtoolError::loader("NeoForge", format!("no NeoForge version is known for Minecraft {}", meta.version)))
+    }
+}
+
+// Returns the installed version name, e.g. `neoforge-20.4.80`
+pub async fn install_neoforge(neoforge_version: String, java: &java::JavaInstall) -> Result<String> {
+    let version_name = format!("neoforge-{}", neoforge_version);
+    if installed_versions()?.contains(&version_name) {
+        return Ok(version_name)
+    }
+
+    let installer_url = format!("{0}/releases/net/neoforged/neoforge/{1}/neoforge-{1}-installer.jar", settings::settings().neoforge_maven, neoforge_version);
+    let filepath = download_installer(installer_url).await?;
+
+    run_installer("NeoForge", java, filepath.as_str(), version_name.as_str()).await?;
+
+    if installed_versions()?.contains(&version_name) {
+        Ok(version_name)
+    } else {
+        Err(ModtoolError::loader("NeoForge", format!("the installer finished without adding {}, see the log in {}", version_name, installer_log_dir().display())))
+    }
+}
+
+// The loader version in the name of a `versions/` folder, if the folder is a loader
+// for the profile's loader and Minecraft version
+pub fn loader_version_of(meta: &ProfileMeta, name: &str) -> Option<String> {
+    let version = match meta.loader {
+        // 1.18.2-forge-40.1.0
+        ModLoader::Forge => name.strip_prefix(format!("{}-forge-", meta.version).as_str()),
+        // neoforge-20.4.80
+        ModLoader::NeoForge => name.strip_prefix("neoforge-")
+            .filter(|version| neoforge_prefix(meta.version.as_str()).map(|prefix| version.starts_with(prefix.as_str())).unwrap_or(false)),
+        // fabric-loader-0.14.8-1.19
+        ModLoader::Fabric => name.strip_prefix("fabric-loader-").and_then(|rest| rest.strip_suffix(format!("-{}", meta.version).as_str())),
+        // quilt-loader-0.19.2-1.20.1
+        ModLoader::Quilt => name.strip_prefix("quilt-loader-").and_then(|rest| rest.strip_suffix(format!("-{}", meta.version).as_str()))
+    };
+    version.map(String::from)
+}
+
+// The `versions/` folder the profile's loader is installed in, and the loader version.
+// A pinned loader version has to match.
+pub fn installed_loader(meta: &ProfileMeta) -> Result<Option<(String, String)>> {
+    let mut found = None;
+    for name in installed_versions()? {
+        if let Some(version) = loader_version_of(meta, name.as_str()) {
+            if meta.loader_version.as_ref().map(|pinned| pinned == &version).unwrap_or(true) {
+                found = Some((name, version));
+            }
+        }
+    }
+    Ok(found)
+}
+
+// Creates the profile's game directory with empty `mods` and `config` folders
+pub fn create_game_dir(meta: &ProfileMeta) -> Result<()> {
+    for dir in [meta.mods_dir(), Path::new(meta.game_dir().as_str()).join("config").display().to_string()] {
+        std::fs::create_dir_all(&dir).map_err(|err| ModtoolError::io(&dir, err))?;
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<usize> {
+    std::fs::create_dir_all(to).map_err(|err| ModtoolError::io(to, err))?;
+    let mut copied = 0;
+    for entry in std::fs::read_dir(from).map_err(|err| ModtoolError::io(from, err))? {
+        let entry = entry.map_err(|err| ModtoolError::io(from, err))?;
+        let target = to.join(entry.file_name());
+        if entry.path().is_dir() {
+            copied += copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target).map_err(|err| ModtoolError::io(&target, err))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+// Copies an imported modpack's config files and the like over the game directory.
+// Returns how many files were copied.
+pub fn apply_overrides(meta: &ProfileMeta) -> Result<usize> {
+    match meta.overrides.as_ref() {
+        Some(overrides) if Path::new(overrides).is_dir() => copy_dir(Path::new(overrides), Path::new(meta.game_dir().as_str())),
+        _ => Ok(0)
+    }
+}
+
+// Whether a failed download is worth trying again
+fn is_transient(err: &ModtoolError) -> bool {
+    match err {
+        ModtoolError::Timeout { .. } => true,
+        ModtoolError::Network(err) => match err.status() {
+            Some(status) => status.is_server_error()
+                || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE,
+            // Only failures to connect or to get the response through. A bad url, a redirect loop and
+            // the like fail the same way every time. Body errors are retried since the download resumes.
+            None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body()
+        },
+        _ => false
+    }
+}
+
+// 0.5s before the first retry, doubling each time up to 32s
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(500 * 2u64.pow(attempt.saturating_sub(1).min(6)))
+}
+
+// Downloads into `partpath`, carrying on from where an earlier attempt stopped if the server supports it
+async fn fetch_part(modinfo: &ModDownload, partpath: &Path, progress: &mut impl FnMut(u64, u64)) -> Result<()> {
+    let existing = std::fs::metadata(partpath).map(|meta| meta.len()).unwrap_or(0);
+    let mut req = HTTP_CLIENT
+        .get(modinfo.url.clone())
+        .header("User-Agent", format!("Starkiller645/modtool-rs/{APP_VERSION} (tallie@tallie.dev)"))
+        .timeout(FILE_DOWNLOAD_TIMEOUT);
+    if existing > 0 {
+        req = req.header("Range", format!("bytes={}-", existing));
+    }
+    // A server that accepts the connection but never answers counts as a timeout, like a stalled download
+    let read_timeout = std::time::Duration::from_secs(settings::settings().read_timeout_secs);
+    let res = match tokio::time::timeout(read_timeout, req.send()).await {
+        Ok(res) => res?,
+        Err(_) => return Err(ModtoolError::Timeout { url: modinfo.url.clone() })
+    };
+    if res.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The part file is stale, start again on the next attempt
+        std::fs::remove_file(partpath).map_err(|err| ModtoolError::io(partpath, err))?;
+    }
+    let res = res.error_for_status()?;
+
+    // Servers that ignore the Range header send the whole file again
+    let resumed = res.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded_bytes = match resumed {
+        true => existing,
+        false => 0
+    };
+    let fhandle = match resumed {
+        true => std::fs::OpenOptions::new().append(true).open(partpath),
+        false => File::create(partpath)
+    };
+    let mut fhandle = fhandle.map_err(|err| ModtoolError::io(partpath, err))?;
+
+    let content_length = match res.content_length() {
+        Some(length) => length + downloaded_bytes,
+        None => modinfo.bytes_total.max(0) as u64
+    };
+    progress(downloaded_bytes, content_length.max(downloaded_bytes));
+
+    let mut bytes_stream = res.bytes_stream();
+    loop {
+        let item = match tokio::time::timeout(read_timeout, bytes_stream.next()).await {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(_) => return Err(ModtoolError::Timeout { url: modinfo.url.clone() })
+        };
+        let chunk = item?;
+        fhandle.write_all(&chunk).map_err(|err| ModtoolError::io(partpath, err))?;
+        downloaded_bytes += chunk.len() as u64;
+        progress(downloaded_bytes, content_length.max(downloaded_bytes));
+    }
+    Ok(())
+}
+
+// Downloads a single mod into `mods_dir`, calling `progress` with (bytes read, bytes total).
+// Network errors are retried with exponential backoff, resuming from a `.part` file.
+// If the manifest gives hashes for the mod, a file that doesn't match them is deleted.
+// Mods in the download cache are checked against their hashes and linked from there instead of being fetched again.
+pub async fn download_mod(modinfo: ModDownload, mods_dir: String, mut progress: impl FnMut(u64, u64)) -> Result<()> {
+    let filename = match mod_filename(&modinfo.filename, &modinfo.url) {
+        Some(filename) => filename,
+        None => return Err(ModtoolError::Manifest(format!("{} has no file name in its url {}", modinfo.name, modinfo.url)))
+    };
+    std::fs::create_dir_all(&mods_dir).map_err(|err| ModtoolError::io(&mods_dir, err))?;
+    let filepath = Path::new(&mods_dir).join(&filename);
+    let partpath = Path::new(&mods_dir).join(format!("{}.part", filename));
+
+    // A broken cache shouldn't stop the install, the mod is just downloaded instead
+    if let Ok(Some(size)) = cache::restore(&modinfo, &filepath) {
+        progress(size, size);
+        return Ok(())
+    }
+
+    match modinfo.manual {
+        // The user has to fetch these themselves, so look for the file in their downloads folder
+        true => {
+            let downloads_dir = downloads_dir()?;
+            let manual = Path::new(&downloads_dir).join(&filename);
+            if !manual.exists() {
+                return Err(ModtoolError::ManualDownload {
+                    name: modinfo.name.clone(),
+                    filename,
+                    page: modinfo.url.clone(),
+                    downloads_dir
+                })
+            }
+            let size = std::fs::copy(&manual, &partpath).map_err(|err| ModtoolError::io(&partpath, err))?;
+            progress(size, size);
+        },
+        false => {
+            let retries = settings::settings().download_retries;
+            let mut attempt = 0;
+            loop {
+                match fetch_part(&modinfo, &partpath, &mut progress).await {
+                    Ok(_) => break,
+                    Err(err) if attempt < retries && is_transient(&err) => {
+                        attempt += 1;
+                        tokio::time::sleep(retry_delay(attempt)).await;
+                    },
+                    Err(err) => return Err(err)
+                }
+            }
+        }
+    }
+
+    // Checked after the download finishes, since a resumed file was written in several goes
+    let mut verifier = Verifier::new(&modinfo.sha1, &modinfo.sha256, &modinfo.sha512);
+    verifier.update_from_file(&partpath).map_err(|err| ModtoolError::io(&partpath, err))?;
+    if let Err(mismatch) = verifier.verify() {
+        std::fs::remove_file(&partpath).map_err(|err| ModtoolError::io(&partpath, err))?;
+        return Err(ModtoolError::HashMismatch {
+            name: modinfo.name,
+            algorithm: mismatch.algorithm,
+            expected: mismatch.expected,
+            actual: mismatch.actual
+        })
+    }
+
+    // It may be hardlinked to a cached jar, which mustn't be overwritten
+    if filepath.exists() {
+        std::fs::remove_file(&filepath).map_err(|err| ModtoolError::io(&filepath, err))?;
+    }
+    std::fs::rename(&partpath, &filepath).map_err(|err| ModtoolError::io(&filepath, err))?;
+
+    if let Err(err) = cache::store(&modinfo, &filepath) {
+        log::warn!("Could not cache {}: {}", modinfo.name, err);
+    }
+    Ok(())
+}
+
+// Downloads a mod and adds it to the profile's install record
+pub async fn install_mod(meta: &ProfileMeta, modinfo: ModDownload, mods_dir: String, progress: impl FnMut(u64, u64)) -> Result<()> {
+    download_mod(modinfo.clone(), mods_dir.clone(), progress).await?;
+    mods_dir::record_download(meta, mods_dir.as_str(), &modinfo)
+}
+
+pub fn mod_download(modinfo: &Mod) -> ModDownload {
+    let modinfo = modinfo.clone();
+    ModDownload {
+        name: modinfo.name,
+        url: modinfo.url,
+        version: modinfo.version,
+        bytes_total: modinfo.size,
+        bytes_read: 0,
+        status: Download::Queued,
+        provider: modinfo.provider,
+        filename: modinfo.filename,
+        manual: modinfo.manual,
+        dependency: false,
+        sha1: modinfo.sha1,
+        sha256: modinfo.sha256,
+        sha512: modinfo.sha512
+    }
+}
+
+pub fn mod_downloads(profile: &Profile) -> Vec<ModDownload> {
+    profile.mods.iter().map(mod_download).collect()
+}
+
+// Adds a `modtool-rs-<loader>-<version>-<id>` entry to the vanilla launcher, or points the existing one
+// at `version`, the `versions/` folder the loader was just installed to. New entries run on `java`.
+pub fn write_launcher_profile(current_profile: &Profile, version: &str, java: &java::JavaInstall) -> Result<()> {
+    let mut launcher_profiles = launcher::load()?;
+
+    let loader: &str = match current_profile.meta.loader {
+        ModLoader::Fabric => "fabric",
+        ModLoader::Forge => "forge",
+        ModLoader::Quilt => "quilt",
+        ModLoader::NeoForge => "neoforge"
+    };
+
+    let key = format!("modtool-rs-{}-{}-{}", loader, current_profile.meta.version, current_profile.meta.id);
+    let game_dir = current_profile.meta.game_dir();
+    // The launcher's own game directory is left out, as it would be for a profile made there
+    let game_dir = match game_dir == MC_DATA.base_dir {
+        true => None,
+        false => Some(game_dir)
+    };
+    let java_dir = Some(java.path.display().to_string());
+
+    match launcher_profiles.profiles.get_mut(&key) {
+        // Point an existing entry at the loader, Java and game directory we just installed for, in case
+        // the old ones are gone, leaving the rest as the user set it
+        Some(profile) => {
+            let mut changed = false;
+            if !version.is_empty() && profile.lastVersionId.as_deref() != Some(version) {
+                profile.lastVersionId = Some(String::from(version));
+                changed = true;
+            }
+            if profile.javaDir != java_dir {
+                profile.javaDir = java_dir;
+                changed = true;
+            }
+            if profile.gameDir != game_dir {
+                profile.gameDir = game_dir;
+                changed = true;
+            }
+            if changed {
+                launcher::save(&launcher_profiles)?;
+            }
+        },
+        None => {
+            let date: DateTime<Utc> = Utc::now();
+            let date_str: String = date.to_rfc3339_opts(SecondsFormat::Millis, true);
+
+            let profile = launcher::LauncherProfile {
+                created: Some(date_str),
+                gameDir: game_dir,
+                lastUsed: Some(String::from("1970-01-02T00:00:00.000Z")),
+                lastVersionId: Some(String::from(version)),
+                javaArgs: Some(String::from(LAUNCHER_JAVA_ARGS)),
+                javaDir: java_dir,
+                name: Some(format!("{} (ModTool RS)", current_profile.meta.name)),
+                r#type: Some(String::from("custom")),
+                icon: Some(String::from(LAUNCHER_ICON)),
+                extra: Default::default()
+            };
+
+            launcher_profiles.profiles.insert(key, profile);
+            launcher::save(&launcher_profiles)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn retry_delay_is_capped() {
+        assert_eq!(retry_delay(1).as_millis(), 500);
+        assert_eq!(retry_delay(3).as_millis(), 2000);
+        assert_eq!(retry_delay(7).as_millis(), 32000);
+        assert_eq!(retry_delay(u32::MAX).as_millis(), 32000);
+    }
+
+    #[test]
+    fn reinstalls_refresh_the_launcher_entry() {
+        test_support::home();
+        let _globals = test_support::blocking_lock_globals();
+        std::fs::write(launcher::profiles_path(), r#"{ "profiles": {} }"#).unwrap();
+        let profile: Profile = serde_json::from_value(serde_json::json!({
+            "meta": { "name": "Refresh", "loader": "Fabric", "version": "1.20.1", "id": 717171 }, "mods": []
+        })).unwrap();
+        let java = |path: &str| java::JavaInstall { path: PathBuf::from(path), major: 17, version: String::from("17.0.2") };
+        let key = "modtool-rs-fabric-1.20.1-717171";
+
+        write_launcher_profile(&profile, "fabric-loader-0.14.21-1.20.1", &java("/old/jdk/bin/java")).unwrap();
+        let entry = launcher::load().unwrap().profiles[key].clone();
+        assert_eq!(entry.javaDir.as_deref(), Some("/old/jdk/bin/java"));
+        assert_eq!(entry.gameDir, Some(profile.meta.game_dir()));
+
+        // The user's own changes survive, what ModTool picked is updated
+        let mut edited = entry.clone();
+        edited.javaArgs = Some(String::from("-Xmx6G"));
+        edited.gameDir = Some(String::from("/moved/away"));
+        launcher::update_profile(key, edited).unwrap();
+        write_launcher_profile(&profile, "fabric-loader-0.15.0-1.20.1", &java("/new/jdk/bin/java")).unwrap();
+        let entry = launcher::load().unwrap().profiles[key].clone();
+        assert_eq!(entry.lastVersionId.as_deref(), Some("fabric-loader-0.15.0-1.20.1"));
+        assert_eq!(entry.javaDir.as_deref(), Some("/new/jdk/bin/java"));
+        assert_eq!(entry.gameDir, Some(profile.meta.game_dir()));
+        assert_eq!(entry.javaArgs.as_deref(), Some("-Xmx6G"));
+    }
+
+    #[tokio::test]
+    async fn only_network_failures_are_retried() {
+        let refused = reqwest::Client::new().get("http://127.0.0.1:1/mod.jar").send().await.unwrap_err();
+        assert!(is_transient(&ModtoolError::Network(refused)));
+        let bad_url = reqwest::Client::new().get("not a url").send().await.unwrap_err();
+        assert!(!is_transient(&ModtoolError::Network(bad_url)));
+        assert!(is_transient(&ModtoolError::Timeout { url: String::new() }));
+        assert!(!is_transient(&ModtoolError::Manifest(String::new())));
+    }
+
+    #[tokio::test]
+    async fn server_that_never_answers_times_out() {
+        test_support::home();
+        let _globals = test_support::lock_globals().await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mod.jar", listener.local_addr().unwrap());
+        // Accept connections and hold them open without ever sending headers
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+        settings::SETTINGS.write().unwrap().read_timeout_secs = 1;
+
+        let modinfo: Mod = serde_json::from_value(serde_json::json!({ "name": "Stalled", "url": url, "provider": "Modrinth" })).unwrap();
+        let partpath = test_support::scratch_dir("stalled").join("mod.jar.part");
+        let started = std::time::Instant::now();
+        let res = fetch_part(&mod_download(&modinfo), &partpath, &mut |_, _| {}).await;
+        assert!(matches!(res, Err(ModtoolError::Timeout { .. })));
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn meta_loaders_pick_stable_builds_from_a_local_server() {
+        test_support::home();
+        let _globals = test_support::lock_globals().await;
+        let fabric = test_support::MockServer::start(vec![
+            ("/versions/loader/1.19.2", 200, test_support::MockServer::json(serde_json::json!([
+                { "loader": { "version": "0.15.0+beta.1", "stable": false } },
+                { "loader": { "version": "0.14.21", "stable": true } }
+            ]))),
+            ("/versions/loader/1.19.2/0.14.21/profile/json", 200, test_support::MockServer::json(serde_json::json!({ "id": "fabric-loader-0.14.21-1.19.2" }))),
+            ("/versions/loader/1.19.3/0.14.21/profile/json", 200, test_support::MockServer::json(serde_json::json!({ "id": "fabric-loader-0.14.20-1.19.3" }))),
+            ("/versions/loader/1.4.7", 200, test_support::MockServer::json(serde_json::json!([])))
+        ]).await;
+        // Quilt doesn't mark stable builds, anything with a `-` in it is a beta
+        let quilt = test_support::MockServer::start(vec![
+            ("/versions/loader/1.20.1", 200, test_support::MockServer::json(serde_json::json!([
+                { "loader": { "version": "0.20.0-beta.2" } },
+                { "loader": { "version": "0.19.2" } }
+            ]))),
+            ("/versions/loader/1.20.1/0.19.2/profile/json", 200, test_support::MockServer::json(serde_json::json!({ "id": "quilt-loader-0.19.2-1.20.1" })))
+        ]).await;
+        settings::SETTINGS.write().unwrap().fabric_meta = fabric.url.clone();
+        settings::SETTINGS.write().unwrap().quilt_meta = quilt.url.clone();
+        let meta = |loader: &str, version: &str, loader_version: Option<&str>| -> ProfileMeta {
+            serde_json::from_value(serde_json::json!({
+                "name": "Meta", "loader": loader, "version": version, "id": 616161, "loader_version": loader_version
+            })).unwrap()
+        };
+        let versions_dir = Path::new(MC_DATA.profiles_dir.as_str());
+
+        assert_eq!(install_fabric(&meta("Fabric", "1.19.2", None)).await.unwrap(), "fabric-loader-0.14.21-1.19.2");
+        assert!(versions_dir.join("fabric-loader-0.14.21-1.19.2").join("fabric-loader-0.14.21-1.19.2.json").is_file());
+        assert_eq!(install_quilt(&meta("Quilt", "1.20.1", None)).await.unwrap(), "quilt-loader-0.19.2-1.20.1");
+        assert!(versions_dir.join("quilt-loader-0.19.2-1.20.1").join("quilt-loader-0.19.2-1.20.1.json").is_file());
+
+        let unknown_loader = install_fabric(&meta("Fabric", "1.19.2", Some("9.9.9"))).await;
+        assert!(matches!(unknown_loader, Err(ModtoolError::LoaderInstall { message, .. }) if message.contains("there is no loader 9.9.9")));
+        let unsupported = install_fabric(&meta("Fabric", "1.4.7", None)).await;
+        assert!(matches!(unsupported, Err(ModtoolError::LoaderInstall { message, .. }) if message.contains("doesn't support Minecraft 1.4.7")));
+        let wrong_profile = install_fabric(&meta("Fabric", "1.19.3", Some("0.14.21"))).await;
+        assert!(matches!(wrong_profile, Err(ModtoolError::LoaderInstall { message, .. }) if message.contains("instead of")));
+        assert!(!versions_dir.join("fabric-loader-0.14.21-1.19.3").exists());
+    }
+
+    #[test]
+    fn neoforge_versions_drop_the_leading_one() {
+        assert_eq!(neoforge_prefix("1.21").as_deref(), Some("21.0."));
+        assert_eq!(neoforge_prefix("1.20.4").as_deref(), Some("20.4."));
+        assert_eq!(neoforge_prefix("1.21.10").as_deref(), Some("21.10."));
+        assert_eq!(neoforge_prefix("23w13a"), None);
+    }
+
+    #[test]
+    fn loader_folders_match_the_profile() {
+        let meta = |loader: &str, version: &str| -> ProfileMeta {
+            serde_json::from_value(serde_json::json!({ "name": "Folders", "loader": loader, "version": version, "id": 1 })).unwrap()
+        };
+        assert_eq!(loader_version_of(&meta("Forge", "1.18.2"), "1.18.2-forge-40.1.0").as_deref(), Some("40.1.0"));
+        assert_eq!(loader_version_of(&meta("Forge", "1.18.2"), "1.19.2-forge-43.1.0"), None);
+        assert_eq!(loader_version_of(&meta("NeoForge", "1.21.1"), "neoforge-21.1.77").as_deref(), Some("21.1.77"));
+        // 21.10.x is for 1.21.10, not 1.21.1
+        assert_eq!(loader_version_of(&meta("NeoForge", "1.21.1"), "neoforge-21.10.5"), None);
+        assert_eq!(loader_version_of(&meta("Fabric", "1.19"), "fabric-loader-0.14.8-1.19").as_deref(), Some("0.14.8"));
+        assert_eq!(loader_version_of(&meta("Fabric", "1.19"), "quilt-loader-0.19.2-1.19"), None);
+        assert_eq!(loader_version_of(&meta("Quilt", "1.20.1"), "quilt-loader-0.19.2-1.20.1").as_deref(), Some("0.19.2"));
+        assert_eq!(loader_version_of(&meta("Quilt", "1.20.1"), "1.20.1"), None);
+    }
+
+    #[tokio::test]
+    async fn neoforge_picks_the_newest_stable_build_then_a_beta() {
+        test_support::home();
+        let _globals = test_support::lock_globals().await;
+        let maven = test_support::MockServer::start(vec![
+            ("/api/maven/versions/releases/net/neoforged/neoforge", 200, test_support::MockServer::json(serde_json::json!({
+                "versions": ["20.4.80-beta", "20.4.190", "20.4.237", "20.4.238-beta", "21.0.1-beta", "21.0.5-beta", "21.1.1"]
+            })))
+        ]).await;
+        settings::SETTINGS.write().unwrap().neoforge_maven = maven.url.clone();
+        let meta = |version: &str, loader_version: Option<&str>| -> ProfileMeta {
+            serde_json::from_value(serde_json::json!({
+                "name": "NeoForge", "loader": "NeoForge", "version": version, "id": 626262, "loader_version": loader_version
+            })).unwrap()
+        };
+
+        assert_eq!(neoforge_version(&meta("1.20.4", None)).await.unwrap(), (String::from("20.4.237"), "NeoForge's latest stable build"));
+        assert_eq!(neoforge_version(&meta("1.21", None)).await.unwrap(), (String::from("21.0.5-beta"), "NeoForge's latest beta"));
+        assert_eq!(neoforge_version(&meta("1.21.1", None)).await.unwrap(), (String::from("21.1.1"), "NeoForge's latest stable build"));
+        assert_eq!(neoforge_version(&meta("1.21.1", Some("21.1.0"))).await.unwrap(), (String::from("21.1.0"), "pinned by the profile"));
+        let unknown = neoforge_version(&meta("1.22", None)).await;
+        assert!(matches!(unknown, Err(ModtoolError::LoaderInstall { message, .. }) if message.contains("no NeoForge version is known")));
+    }
+}
@@ -0,0 +1,392 @@
+// Finds Java installations (JAVA_HOME, PATH, the usual JVM folders and the runtimes the Minecraft
+// launcher downloads for itself) and picks one that can run a profile's Minecraft version.
+// When none can, a Temurin JRE is downloaded into CONFIG_DIR/runtimes.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::settings::settings;
+use std::path::PathBuf;
+
+// Temurin only publishes LTS releases
+static TEMURIN_RELEASES: &[u32] = &[8, 11, 17, 21, 25];
+
+#[derive(Clone, PartialEq)]
+pub struct JavaInstall {
+    // The `java` executable
+    pub path: PathBuf,
+    pub major: u32,
+    // As `java -version` prints it, e.g. `17.0.2` or `1.8.0_301`
+    pub version: String
+}
+
+fn executable_name() -> &'static str {
+    match cfg!(windows) {
+        true => "java.exe",
+        false => "java"
+    }
+}
+
+// Folders that hold JDKs and JREs, one per subfolder
+fn jvm_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    match cfg!(windows) {
+        true => {
+            for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+                if let Some(program_files) = env::var_os(var) {
+                    for vendor in ["Java", "Eclipse Adoptium", "Eclipse Foundation", "AdoptOpenJDK", "Microsoft", "Zulu", "Amazon Corretto", "BellSoft"] {
+                        dirs.push(Path::new(&program_files).join(vendor));
+                    }
+                }
+            }
+        },
+        false => {
+            for dir in ["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/opt/java", "/opt/jdk", "/Library/Java/JavaVirtualMachines"] {
+                dirs.push(PathBuf::from(dir));
+            }
+            if let Some(home) = env::var_os("HOME") {
+                dirs.push(Path::new(&home).join(".sdkman/candidates/java"));
+                dirs.push(Path::new(&home).join(".jdks"));
+                dirs.push(Path::new(&home).join("Library/Java/JavaVirtualMachines"));
+            }
+        }
+    }
+    dirs
+}
+
+// Runtimes we downloaded ourselves, one folder per major version
+pub fn runtimes_dir() -> PathBuf {
+    Path::new(CONFIG_DIR.as_str()).join("runtimes")
+}
+
+// Where the Minecraft launcher keeps the runtimes it downloads
+fn launcher_runtime_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![Path::new(MC_DATA.base_dir.as_str()).join("runtime")];
+    if cfg!(windows) {
+        if let Some(local) = env::var_os("LOCALAPPDATA") {
+            dirs.push(Path::new(&local).join("Packages\\Microsoft.4297127D64EC6_8wekyb3d8bbwe\\LocalCache\\Local\\runtime"));
+        }
+        if let Some(program_files) = env::var_os("ProgramFiles(x86)") {
+            dirs.push(Path::new(&program_files).join("Minecraft Launcher\\runtime"));
+        }
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(Path::new(&home).join("Library/Application Support/minecraft/runtime"));
+    }
+    dirs
+}
+
+// Looks for `bin/java` in `dir` and its subfolders, up to `depth` levels down.
+// Runtimes can be nested, e.g. `java-runtime-gamma/linux/java-runtime-gamma/bin/java`,
+// or `Contents/Home/bin/java` on macOS.
+fn find_executables(dir: &Path, depth: u32, found: &mut Vec<PathBuf>) {
+    let executable = dir.join("bin").join(executable_name());
+    if executable.is_file() {
+        found.push(executable);
+        return
+    }
+    if depth == 0 {
+        return
+    }
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if entry.path().is_dir() {
+                find_executables(&entry.path(), depth - 1, found);
+            }
+        }
+    }
+}
+
+fn candidates() -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        find_executables(Path::new(&java_home), 0, &mut found);
+    }
+    if let Some(path) = env::var_os("PATH") {
+        for dir in env::split_paths(&path) {
+            let executable = dir.join(executable_name());
+            if executable.is_file() {
+                found.push(executable);
+            }
+        }
+    }
+    for dir in jvm_dirs() {
+        find_executables(&dir, 3, &mut found);
+    }
+    for dir in launcher_runtime_dirs() {
+        find_executables(&dir, 4, &mut found);
+    }
+    find_executables(&runtimes_dir(), 3, &mut found);
+    found
+}
+
+// Reads the version from `java -version`, whose first line is e.g.
+// `openjdk version "17.0.2" 2022-01-18` or `java version "1.8.0_301"`
+pub fn parse_version(output: &str) -> Option<(u32, String)> {
+    let line = output.lines().find(|line| line.contains("version \""))?;
+    let version = line.split('"').nth(1)?;
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = match parts.next()?.parse::<u32>().ok()? {
+        // Java 8 and older call themselves 1.x
+        1 => parts.next()?.parse::<u32>().ok()?,
+        major => major
+    };
+    Some((major, String::from(version)))
+}
+
+fn probe(path: &Path) -> Option<JavaInstall> {
+    let mut command = process::Command::new(path);
+    command.arg("-version");
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let output = command.output().ok()?;
+    // Java prints its version to stderr, some wrappers use stdout
+    let text = String::from_utf8_lossy(&output.stderr).to_string() + String::from_utf8_lossy(&output.stdout).as_ref();
+    let (major, version) = parse_version(text.as_str())?;
+    Some(JavaInstall { path: path.to_path_buf(), major, version })
+}
+
+// Every Java that runs, newest first. The same installation reached through
+// different paths (like a symlink on PATH) is only listed once.
+pub fn discover() -> Vec<JavaInstall> {
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut installs = Vec::new();
+    for path in candidates() {
+        let real = std::fs::canonicalize(&path).unwrap_or(path.clone());
+        if seen.contains(&real) {
+            continue
+        }
+        seen.push(real);
+        if let Some(install) = probe(&path) {
+            installs.push(install);
+        }
+    }
+    installs.sort_by_key(|install| std::cmp::Reverse(install.major));
+    installs
+}
+
+// The oldest Java a Minecraft version runs on
+pub fn required_major(mc_version: &str) -> u32 {
+    let mut parts = match mc_version.strip_prefix("1.") {
+        Some(rest) => rest.split('.').map(|part| part.parse::<u32>().unwrap_or(0)),
+        // Snapshots and anything newer than we know about
+        None => return 21
+    };
+    let minor = parts.next().unwrap_or(0);
+    let patch = parts.next().unwrap_or(0);
+    match (minor, patch) {
+        (21.., _) | (20, 5..) => 21,
+        (18.., _) => 17,
+        (17, _) => 16,
+        _ => 8
+    }
+}
+
+// The installed Java closest to what the Minecraft version needs without going under it,
+// since old modded versions often break on much newer Java
+pub fn select(installs: &[JavaInstall], mc_version: &str) -> Option<JavaInstall> {
+    let required = required_major(mc_version);
+    installs.iter()
+        .filter(|install| install.major >= required)
+        .min_by_key(|install| install.major)
+        .cloned()
+}
+
+pub fn find_for(meta: &ProfileMeta) -> Result<JavaInstall> {
+    let installs = discover();
+    if installs.is_empty() {
+        return Err(ModtoolError::JavaNotFound)
+    }
+    select(&installs, meta.version.as_str()).ok_or(ModtoolError::JavaTooOld {
+        mc_version: meta.version.clone(),
+        required: required_major(meta.version.as_str()),
+        newest: installs[0].major
+    })
+}
+
+// The runtime `install_runtime` downloads for a required version
+pub fn runtime_major(required: u32) -> u32 {
+    TEMURIN_RELEASES.iter().copied().find(|major| *major >= required).unwrap_or(required)
+}
+
+#[derive(Deserialize)]
+struct TemurinAsset {
+    binary: TemurinBinary
+}
+
+#[derive(Deserialize)]
+struct TemurinBinary {
+    package: TemurinPackage
+}
+
+#[derive(Deserialize)]
+struct TemurinPackage {
+    checksum: String,
+    link: String,
+    name: String
+}
+
+// Adoptium's names for this OS and architecture
+fn temurin_platform() -> (&'static str, &'static str) {
+    let os = match env::consts::OS {
+        "windows" => "windows",
+        "macos" => "mac",
+        _ => "linux"
+    };
+    let arch = match env::consts::ARCH {
+        "aarch64" => "aarch64",
+        "x86" => "x32",
+        _ => "x64"
+    };
+    (os, arch)
+}
+
+fn unpack(name: &str, data: &[u8], dest: &Path) -> Result<()> {
+    match name.ends_with(".zip") {
+        true => zip::ZipArchive::new(Cursor::new(data))?.extract(dest)?,
+        false => tar::Archive::new(flate2::read::GzDecoder::new(data)).unpack(dest)
+            .map_err(|err| ModtoolError::io(dest, err))?
+    }
+    Ok(())
+}
+
+// Unpacks a runtime archive into `staging` and moves it to `target`, replacing what was there
+fn unpack_runtime(name: &str, data: &[u8], staging: &Path, target: &Path) -> Result<()> {
+    let _ = std::fs::remove_dir_all(staging);
+    std::fs::create_dir_all(staging).map_err(|err| ModtoolError::io(staging, err))?;
+    unpack(name, data, staging)?;
+
+    // Archives hold a single `jdk-17.0.8+7-jre` folder, which becomes the runtime folder
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(staging).map_err(|err| ModtoolError::io(staging, err))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    let root = match entries.len() == 1 && entries[0].is_dir() {
+        true => entries.remove(0),
+        false => staging.to_path_buf()
+    };
+    if target.exists() {
+        std::fs::remove_dir_all(target).map_err(|err| ModtoolError::io(target, err))?;
+    }
+    std::fs::rename(&root, target).map_err(|err| ModtoolError::io(target, err))
+}
+
+// Downloads the Temurin JRE for `required` (or the next LTS after it) from the Adoptium API in
+// settings, checks it against the API's sha256 and unpacks it to CONFIG_DIR/runtimes/<major>
+pub async fn install_runtime(required: u32) -> Result<JavaInstall> {
+    let major = runtime_major(required);
+    let (os, arch) = temurin_platform();
+    let url = format!("{}/assets/latest/{}/hotspot?architecture={}&image_type=jre&os={}&vendor=eclipse",
+        settings().java_runtime_api.trim_end_matches('/'), major, arch, os);
+    let assets: Vec<TemurinAsset> = HTTP_CLIENT
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let package = match assets.into_iter().next() {
+        Some(asset) => asset.binary.package,
+        None => return Err(ModtoolError::RuntimeDownload { major, message: format!("there's no Temurin {} JRE for {} {}", major, os, arch) })
+    };
+
+    let data = HTTP_CLIENT
+        .get(package.link.as_str())
+        .timeout(FILE_DOWNLOAD_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let actual = hashes::sha256_hex(&data);
+    if actual != package.checksum.to_lowercase() {
+        return Err(ModtoolError::HashMismatch { name: package.name, algorithm: "sha256", expected: package.checksum.to_lowercase(), actual })
+    }
+
+    // Unpack into a staging folder next to the target, which is removed whether or not that worked,
+    // so a broken archive never leaves half a runtime behind
+    let runtimes = runtimes_dir();
+    let staging = runtimes.join(format!("{}.part", major));
+    let target = runtimes.join(major.to_string());
+    let res = unpack_runtime(package.name.as_str(), &data, &staging, &target);
+    let _ = std::fs::remove_dir_all(&staging);
+    res?;
+
+    let mut found = Vec::new();
+    find_executables(&target, 3, &mut found);
+    match found.first().and_then(|path| probe(path)) {
+        Some(install) => Ok(install),
+        None => Err(ModtoolError::RuntimeDownload { major, message: format!("{} has no Java that runs", package.name) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    // A JRE archive whose `java` is a script that only knows how to print its version
+    #[cfg(unix)]
+    fn fake_jre(major: u32) -> Vec<u8> {
+        let script = format!("#!/bin/sh\necho 'openjdk version \"{}.0.1\" 2023-10-17' >&2\n", major);
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(script.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append_data(&mut header, format!("jdk-{}.0.1+12-jre/bin/java", major), script.as_bytes()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn required_versions() {
+        assert_eq!(required_major("1.16.5"), 8);
+        assert_eq!(required_major("1.17.1"), 16);
+        assert_eq!(required_major("1.20.4"), 17);
+        assert_eq!(required_major("1.20.5"), 21);
+        assert_eq!(required_major("24w14a"), 21);
+        assert_eq!(runtime_major(16), 17);
+        assert_eq!(parse_version("java version \"1.8.0_301\"\n"), Some((8, String::from("1.8.0_301"))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn downloads_runtimes_from_a_local_server() {
+        test_support::home();
+        let _globals = test_support::lock_globals().await;
+        let archive = fake_jre(21);
+        // Matches its checksum but isn't a gzip file
+        let broken = b"not a tarball".to_vec();
+        let files = MockServer::start(vec![("/jre.tar.gz", 200, archive.clone()), ("/broken.tar.gz", 200, broken.clone())]).await;
+        let package = |file: &str, checksum: String| MockServer::json(serde_json::json!([{
+            "binary": { "package": { "checksum": checksum, "link": format!("{}/{}", files.url, file), "name": file } }
+        }]));
+        let asset = |checksum: String| package("jre.tar.gz", checksum);
+        let api = MockServer::start(vec![
+            ("/assets/latest/21/hotspot", 200, asset(hashes::sha256_hex(&archive))),
+            ("/assets/latest/22/hotspot", 200, package("broken.tar.gz", hashes::sha256_hex(&broken))),
+            ("/assets/latest/17/hotspot", 200, asset(String::from("00").repeat(32))),
+            ("/assets/latest/11/hotspot", 200, MockServer::json(serde_json::json!([])))
+        ]).await;
+        settings::SETTINGS.write().unwrap().java_runtime_api = api.url.clone();
+
+        let install = install_runtime(21).await.unwrap();
+        assert_eq!(install.major, 21);
+        assert_eq!(install.path, runtimes_dir().join("21").join("bin").join("java"));
+        assert!(!runtimes_dir().join("21.part").exists());
+
+        let mismatch = install_runtime(17).await;
+        assert!(matches!(mismatch, Err(ModtoolError::HashMismatch { algorithm: "sha256", .. })));
+        assert!(!runtimes_dir().join("17").exists());
+        assert!(!runtimes_dir().join("17.part").exists());
+
+        assert!(install_runtime(22).await.is_err());
+        assert!(!runtimes_dir().join("22").exists());
+        assert!(!runtimes_dir().join("22.part").exists());
+
+        let none_published = install_runtime(11).await;
+        assert!(matches!(none_published, Err(ModtoolError::RuntimeDownload { major: 11, .. })));
+        // Nothing is served for Java 8 at all
+        assert!(install_runtime(8).await.is_err());
+        assert!(!runtimes_dir().join("8").exists());
+    }
+}
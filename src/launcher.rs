@@ -0,0 +1,241 @@
+// The vanilla launcher's `launcher_profiles.json`. Only the profile fields ModTool reads or writes are
+// typed; everything else, including keys added by newer launchers, is kept in `extra` and written
+// back unchanged.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LauncherProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gameDir: Option<String>,
+    // Should be a base64 encoded PNG, or the name of one of the launcher's own icons
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub javaArgs: Option<String>,
+    // Despite the name, the launcher wants the `java` executable here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub javaDir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastUsed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lastVersionId: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LauncherProfiles {
+    #[serde(default)]
+    pub profiles: HashMap<String, LauncherProfile>,
+    // `settings`, `version`, `authenticationDatabase` and whatever else the launcher keeps here
+    #[serde(flatten)]
+    pub extra: Map<String, Value>
+}
+
+pub fn profiles_path() -> PathBuf {
+    Path::new(MC_DATA.base_dir.as_str()).join("launcher_profiles.json")
+}
+
+pub fn load() -> Result<LauncherProfiles> {
+    let path = profiles_path();
+    let data = std::fs::read_to_string(&path).map_err(|err| ModtoolError::io(&path, err))?;
+    Ok(serde_json::from_str(data.as_str())?)
+}
+
+// Writes to a temporary file and renames it over the old one, so the launcher never sees a half
+// written file. The previous version is kept as `launcher_profiles.json.bak`.
+pub fn save(profiles: &LauncherProfiles) -> Result<()> {
+    let path = profiles_path();
+    let tmp_path = path.with_extension("json.tmp");
+    let backup_path = path.with_extension("json.bak");
+
+    let data = serde_json::to_string_pretty(profiles)?;
+    let mut file = File::create(&tmp_path).map_err(|err| ModtoolError::io(&tmp_path, err))?;
+    file.write_all(data.as_bytes()).map_err(|err| ModtoolError::io(&tmp_path, err))?;
+    file.sync_all().map_err(|err| ModtoolError::io(&tmp_path, err))?;
+    drop(file);
+
+    if path.exists() {
+        std::fs::copy(&path, &backup_path).map_err(|err| ModtoolError::io(&backup_path, err))?;
+    }
+    std::fs::rename(&tmp_path, &path).map_err(|err| ModtoolError::io(&path, err))?;
+    Ok(())
+}
+
+// Load, change and save in one go, so anything the launcher wrote in the meantime is kept
+pub fn update_profile(key: &str, profile: LauncherProfile) -> Result<()> {
+    let mut profiles = load()?;
+    profiles.profiles.insert(String::from(key), profile);
+    save(&profiles)
+}
+
+pub fn remove_profile(key: &str) -> Result<()> {
+    let mut profiles = load()?;
+    profiles.profiles.remove(key);
+    save(&profiles)
+}
+
+// Entries ModTool made, keyed `modtool-rs-<loader>-<version>-<id>`, sorted by key
+pub fn modtool_profiles(profiles: &LauncherProfiles) -> Vec<(String, LauncherProfile)> {
+    let mut entries: Vec<(String, LauncherProfile)> = profiles.profiles.iter()
+        .filter(|(key, _)| key.starts_with("modtool-rs-"))
+        .map(|(key, profile)| (key.clone(), profile.clone()))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+// The manifest profile an entry was made for
+pub fn profile_id(key: &str) -> Option<i32> {
+    key.rsplit('-').next()?.parse().ok()
+}
+
+// Whether the `versions/` folder the entry launches is still there
+pub fn version_exists(profile: &LauncherProfile) -> bool {
+    match profile.lastVersionId.as_ref() {
+        Some(version) if !version.is_empty() => Path::new(MC_DATA.profiles_dir.as_str()).join(version).is_dir(),
+        _ => false
+    }
+}
+
+// The `-Xmx` heap limit in javaArgs, in megabytes
+pub fn max_memory_mb(java_args: &str) -> Option<u64> {
+    let value = java_args.split_whitespace().find_map(|arg| arg.strip_prefix("-Xmx"))?;
+    let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len()));
+    let number: u64 = number.parse().ok()?;
+    match unit.to_lowercase().as_str() {
+        "" => Some(number / (1024 * 1024)),
+        "k" => Some(number / 1024),
+        "m" => Some(number),
+        "g" => Some(number * 1024),
+        _ => None
+    }
+}
+
+// javaArgs with its `-Xmx` replaced, or added in front if there wasn't one
+pub fn with_max_memory(java_args: &str, mb: u64) -> String {
+    let xmx = format!("-Xmx{}M", mb);
+    let mut args: Vec<String> = java_args.split_whitespace().map(String::from).collect();
+    match args.iter().position(|arg| arg.starts_with("-Xmx")) {
+        Some(i) => args[i] = xmx,
+        None => args.insert(0, xmx)
+    }
+    args.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn save_keeps_unknown_keys_and_the_previous_file() {
+        test_support::home();
+        let _globals = test_support::blocking_lock_globals();
+        let original = r#"{
+  "authenticationDatabase": { "abc": { "username": "someone@example.com" } },
+  "clientToken": "token",
+  "launcherVersion": { "format": 21, "name": "2.3.173", "profilesFormat": 2 },
+  "profiles": {
+    "vanilla": {
+      "created": "2022-01-01T00:00:00.000Z",
+      "lastVersionId": "latest-release",
+      "name": "",
+      "resolution": { "height": 720, "width": 1280 },
+      "skipJreVersionCheck": true,
+      "type": "latest-release"
+    }
+  },
+  "settings": { "crashAssistance": true, "enableAdvanced": false },
+  "version": 3
+}"#;
+        let path = profiles_path();
+        std::fs::write(&path, original).unwrap();
+
+        let profiles = load().unwrap();
+        save(&profiles).unwrap();
+        let expected: Value = serde_json::from_str(original).unwrap();
+        let saved: Value = serde_json::from_str(std::fs::read_to_string(&path).unwrap().as_str()).unwrap();
+        for (key, value) in expected.as_object().unwrap() {
+            assert_eq!(saved[key].to_string(), value.to_string(), "{}", key);
+        }
+        assert_eq!(saved, expected);
+        assert_eq!(std::fs::read_to_string(path.with_extension("json.bak")).unwrap(), original);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let first_save = std::fs::read_to_string(&path).unwrap();
+        save(&profiles).unwrap();
+        assert_eq!(std::fs::read_to_string(path.with_extension("json.bak")).unwrap(), first_save);
+    }
+
+    #[test]
+    fn update_and_remove_only_touch_their_entry() {
+        test_support::home();
+        let _globals = test_support::blocking_lock_globals();
+        let path = profiles_path();
+        std::fs::write(&path, r#"{ "profiles": { "vanilla": { "name": "Vanilla", "type": "latest-release" } }, "version": 3 }"#).unwrap();
+
+        let key = "modtool-rs-fabric-1.19.2-42";
+        let mut profile: LauncherProfile = serde_json::from_value(serde_json::json!({ "name": "Test", "lastVersionId": "fabric-loader-0.14.21-1.19.2" })).unwrap();
+        update_profile(key, profile.clone()).unwrap();
+        profile.javaArgs = Some(String::from("-Xmx4G"));
+        update_profile(key, profile).unwrap();
+
+        let profiles = load().unwrap();
+        assert_eq!(profiles.profiles.len(), 2);
+        assert_eq!(profiles.profiles[key].javaArgs.as_deref(), Some("-Xmx4G"));
+        assert_eq!(profiles.profiles["vanilla"].name.as_deref(), Some("Vanilla"));
+        assert_eq!(profiles.extra["version"], 3);
+        let entries = modtool_profiles(&profiles);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, key);
+
+        remove_profile(key).unwrap();
+        let profiles = load().unwrap();
+        assert!(!profiles.profiles.contains_key(key));
+        assert!(profiles.profiles.contains_key("vanilla"));
+        // Removing it again is fine
+        remove_profile(key).unwrap();
+    }
+
+    #[test]
+    fn profile_ids_come_from_the_key() {
+        assert_eq!(profile_id("modtool-rs-fabric-1.19.2-42"), Some(42));
+        assert_eq!(profile_id("modtool-rs-neoforge-1.21-1000000001"), Some(1000000001));
+        assert_eq!(profile_id("modtool-rs-forge-1.18.2-"), None);
+        assert_eq!(profile_id("vanilla"), None);
+    }
+
+    #[test]
+    fn reads_the_heap_limit() {
+        assert_eq!(max_memory_mb("-Xmx4G"), Some(4096));
+        assert_eq!(max_memory_mb("-Xmx4g"), Some(4096));
+        assert_eq!(max_memory_mb("-Xmx4096M"), Some(4096));
+        assert_eq!(max_memory_mb("-Xmx4096m"), Some(4096));
+        assert_eq!(max_memory_mb("-Xmx4194304k"), Some(4096));
+        assert_eq!(max_memory_mb("-Xmx4294967296"), Some(4096));
+        assert_eq!(max_memory_mb("-Xms1G -XX:+UseG1GC -Xmx2G"), Some(2048));
+        assert_eq!(max_memory_mb("-Xms1G -XX:+UseG1GC"), None);
+        assert_eq!(max_memory_mb(""), None);
+        assert_eq!(max_memory_mb("-Xmx"), None);
+        assert_eq!(max_memory_mb("-Xmx4T"), None);
+    }
+
+    #[test]
+    fn replaces_only_the_heap_limit() {
+        assert_eq!(with_max_memory("-Xms1G -Xmx2G -XX:+UseG1GC", 4096), "-Xms1G -Xmx4096M -XX:+UseG1GC");
+        assert_eq!(with_max_memory("-XX:+UseG1GC  -Dfoo=bar", 4096), "-Xmx4096M -XX:+UseG1GC -Dfoo=bar");
+        assert_eq!(with_max_memory("", 2048), "-Xmx2048M");
+        assert_eq!(max_memory_mb(with_max_memory("-Xmx2G", 3072).as_str()), Some(3072));
+    }
+}
@@ -0,0 +1,48 @@
+// Sends `log::warn!` and friends to stderr and to CACHE_DIR/logs/modtool.log. The Windows GUI
+// has no console, so the file is the only place its warnings end up.
+
+use crate::*;
+use std::sync::Mutex;
+
+struct Logger {
+    file: Mutex<Option<File>>
+}
+
+lazy_static! {
+    static ref LOGGER: Logger = Logger {
+        file: Mutex::new(None)
+    };
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return
+        }
+        let line = format!("{} {} {}: {}", Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true), record.level(), record.target(), record.args());
+        eprintln!("{}", line);
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+pub fn init() {
+    let dir = install::installer_log_dir();
+    let file = std::fs::create_dir_all(&dir).ok()
+        .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(dir.join("modtool.log")).ok());
+    *LOGGER.file.lock().unwrap() = file;
+    if log::set_logger(&*LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+}
@@ -6,14 +6,12 @@ use fermi::*;
 use std::fs::File;
 use std::path::Path;
 use std::io::{Cursor, Write};
-use std::io::prelude::*;
 use chrono::{DateTime, Utc, SecondsFormat};
 use std::collections::HashMap;
 use std::process;
 use std::rc::Rc;
 use std::env;
 use serde::{Serialize, Deserialize};
-use std::cmp::Ordering;
 use async_std::stream::StreamExt;
 use lazy_static::*;
 
@@ -22,6 +20,30 @@ use std::os::windows::process::CommandExt;
 
 static APP_VERSION: &'static str = "2.1.0";
 
+static LAUNCHER_JAVA_ARGS: &'static str = "-Dterminal.jline=false -Dterminal.ansi=true -XX:+UseG1GC -XX:+ParallelRefProcEnabled -XX:MaxGCPauseMillis=200 -XX:+UnlockExperimentalVMOptions -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:G1HeapWastePercent=5 -XX:G1MixedGCCountTarget=4 -XX:G1MixedGCLiveThresholdPercent=90 -XX:G1RSetUpdatingPauseTimePercent=5 -XX:SurvivorRatio=32 -XX:+PerfDisableSharedMem -XX:MaxTenuringThreshold=1 -XX:G1NewSizePercent=30 -XX:G1MaxNewSizePercent=40 -XX:G1HeapRegionSize=8M -XX:G1ReservePercent=20 -XX:InitiatingHeapOccupancyPercent=15 -Dusing.aikars.flags=https://mcflags.emc.gs -Daikars.new.flags=true";
+
+static LAUNCHER_ICON: &'static str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAJYAAACWCAYAAAA8AXHiAAAABHNCSVQICAgIfAhkiAAADxVJREFUeF7tnXtwVNUdx89Z8gACCRFQEkgCQ8RsfI2ItpMExD6odHDEx4za1vGNU/tPp9rRoX+IM9XqjLb9p04VbXWsaKtSHJ1RqS0qCa0Uqc9sSGOFDWxECIEEJJBkb7+/a27cxE129+459/k7M3eysPf+7jnf+9nf+d3fOedeKRSV+vr6omPJ8lXJiNEQMURlUsiKiBCVhhAVOMV0RadhM2oU6JNCdCWFSESE0ZWU+JuU20oiPa+0traeVHEK2Ldfqs9uKjcGjRVCGCukkD+Apcn2rfGRHlCg3xDGBiHkZlkgN8c/bO6xWydbYNVEl1YY0rhNGMatOHGl3ZPzcZ5WICGkXC8N+eie2NauXGuaE1gmUMbQGpxwDQOVq9S+3d8WYFmDVRVtul0K4xcMlG8BybfiCUPI+zpjzY9kYygrsKqjjeth7JZsDPI+gVfg8XishUKgCUtGsKqjDe8imFucyRB/HyYFjJ3x2LbzJ2rxhGDBUyFbwIUVSK8APNe4/Iz7BaDaBHOXsaiswAQKvAS4Vqf7Pi1Y1dGmu5CbeoAlZQUyKyDvjseaHxy739fAqq5rvF5I8WRmg7wHKzCsgCFuiLe1PJWqxyiwzDyVSO7ADpz0ZGpyUSAhRWRJaiJ1FFjV9U3rkE2/JxeLvC8rYCog5b3x1uZ1lhojYLG3YkDyVGCU1xoBq7qu4R5QN0Jcnifhw8OoQIrXMsGaW3fhzEmy8AOOrcJIg9I2J4aMgXP2tW3vNsGqqmu4QUr5R6WnYGOhVMAwjBs727Y9aYJVXd/4gjDElaFUghutVgEpXoy3tlwla2tXFp8s7O2F9SK1Z2BrIVXgZNFAaamsijZeDbf1XEhF4GZrUAADzNfIqvqGhzBL8A4N9tlkSBXA7OKHZU20YQMmcF0bUg242RoUwITQZ6kr3IKucLkG+2wypAqgK3wTHqtxFz4sCqkG3GwNCsBRtUvMu6I7Ql73l0Hg6ZOGxILJ/WJ+8QlRhs8d/ZNF/ESx2HeSb6bTSNdHYPEs0TTKFEpDLCvtFReVHRFnTf1CVAOodOXI0CSxG5C19E0Xbx8pM4HjgjFpBms0BhdMOyqWAaaLAFVlUe6Lgv8JwN4CYG/3lorPBwpDyxiDhUt/xpTjpncioKL4rKIcT0bEW70A7EipCVk//h2mElqwKuCNTJiwfWN6n9Zr/hk8F3WTBNi/4NHCUEIF1tRI0oyZLKCK8W+nS/vxKSZgtLV+MdXp0zt2vlCA1QiPRN0cATW7cMAxcTOdaMfRaSZgFJMF7e4ysGDRnZzlnRYiTeDlMmjIEcAItD7cafq9BAosSglYKYLzSo758tocGiww47G3ANhWbH4tvgdrRsHgSMxEHirjMwN8dKV2IwFrpS7eP1bio5r7NI9FN+5WzET5pjLAFfTyAcAiL0Zd5ac+SML6ymMtRvdmATVeJjzogFH7qIs07yzRZXaj6/Ri8TxYtQi8LZgoIOfylQIU5FuAkTejmwCvFE+CRSkB6uIIqAbNyUs7F4K6IhqQ9lKhdIUV9FMaw+3iGbAoWWne0Q0DRclML5XtyJi/eniGeLWn3PQMdRj6uaS8R6zENtNjMV6MkrDDd5aUkHWjuA4Wjc1desohE6o5NgZ9dYrWhovyGkAimCaKZWjgmgAj0IowK8JL5R38IP4OyDZ2z3S0Wq6BRUBdPrNbXIHNSyWBLuW1Yc9k5+7r2+i+CbLl+OulQl7sr4DLKcBcAevm0/aLH8/5zDO6UxC8eRim9xTli0owGXDFjMPiEmznw6N5pRBgd++er30IyXGwrpl1UNw5d5/rOlOH9QZgIu9ESUidhWZSfBeArcR2uqJpOfnUl3JiPwNchzWmKhwFaxViqXVVnflokvexNG3F8k4DLtye09wvEzJ0l6e5OCDegvTEnYBLlwaOgUUx1dOL2vMGw46BjzE9hbwT3dUd9NCsTgr6CTIK+t24C6Z46/698+xImvEYx8BaO2+vo4E6LXQgmF7H9okPhkAo6P8OICPQnCzXtS8SFHepLo6A5ZS36kHMQDD9DdtORUG4asEz2ZuGoN8EDKDpntlKddHltRwBS6e3ohiBYKJczZuag/BMUKj+nhZzfAnZYRGdqmYufro66vBajoC1sa5t3OVTdi9GM4LPNwASeacTIVioQJl+q6ucqziR/CvEWS8qTqBqB4uGO14/82O7/Iw6juYkEUzkoQ54KAhX0rgcjFyIoJ8go60UXWe+5QVA9YDiIF47WEsgwu8XfmK77f9D4E0gEVD0mctoBUzAhgN/u9pQXuumjlq7h6c9TjtYP5p9QPy0MqGk0uyxvpRRtceiNZBLPzxbyTWyjGgHaw2Gb9ZoGL7hGEspB2LJ++cqNehbsCwV+K5QDQ8M1gQ6ch7LPmQMVpbaceY9S6GGd2OwctPL3JvHCjOLxmBl1mjCPXh2Q3p5GKw8wbIOD/N8rHQSMliKwEo1E6YZpOPJx2BpACvVZFDnvGeSjcHKpJDC74OwSidbORisbJVSvJ+f1hXaaTqDZUc1xcd4cSX0Tqx+pmX25GUftTHoz2AphsTP5qwVz/T8hl3D04tpqRmD5eer6lLd92Me2maszCbvlG4NJIPl0oXx42npWRGbDp1iPo9hW4anLjNYfrzCDteZ1j/+gx7ugS3b9X8MlsMXyS+nexdB+Cvo6sg70etUci0MVq6KBXh/etbo8wdnmQ9R68rzpU8MVoBByaZpNIxEMNGzI1TO5WewslE/gPu8hCCcHif0kaa3UjBYAYRmvCbR/PznARM9iEN3YbB0K+yyfUpeUlf3MjyUk8/4Y7BcvvA6Tk+LaP8CmMg7HbVxR6eiTgyWChU9ZoNSBbd9stDVWjFYrsqv5+QM1le6+n5doR5E7FllsBgse+RkOIrBYrAYrDEK8HwsLUioMcoeiz2WGpLGWGGwGCwGi7tCLQxoMcoeiz0Wg8UeSwsDWoyyx2KPxWCxx9LCgBaj7LHYYzFY7LG0MKDFKHss9lgMFnssLQxoMcoeiz0Wg8UeSwsDWoyyx2KPxWCxx9LCgBaj7LHYYzFY7LG0MKDFKHss9lgMFnssLQxoMcoeiz0Wg8UeSwsDWoyyx3LQY30P7yy+r2aPlgvpNaNugTU5khRXzewWV806KObZeMP9XjzsbXUsqlRO7Suhayf3i+fO2KW00l415jRYl51yyIQpOuV4XpJswWMpf757fl42xh6sHaxJ0hBbzvpITMWvKujFCbC+hTfWE0z0wnFV5bHP5ojH9p+mypxpRztYdJInajvEuSXHlFbci8Z0gXUBICKYLi7tFRH8UFUX8lbktVQWR8C6Av3/2nl7Vdbbk7ZUglWH7u1K6LYcHqq8YFBbe+kBcde1L1Ju3xGwqNZPL2rPOxZQ3nrFBvMFay4C78sB08WAqab4hOLapTd3/955YiMeFKe6OAZWGLyWHbBKJw2J1eSZ0M2d43C4oMtbORZjWb+Gh+bvNl17UEsuYNEdHXmmJgDlVlm7p0bQ2y90FMc8FlW+BL/OXwMuepxhEEsmsAgk+mHRX7fvkn+JLnCThi7Quq6OgkUnPbVwQPxmwafijDxzL14EMx1Yi/Ejors5AqrCRvJSRzt/m6gUfzowW4fpEZuOg0VnXoCk6Y2nfi6+X96jtXFOG7fAoqSw5Zm89APai1erPENPdcamu7gCltWoZfgl/3D2gcB0jQTWEBqnMnmpAoCTeC3dBnioZ7D1DBaoMJnRhqtgWbUjz7UUkBFoxSHI0Ge8Kop2aEeOaivehkHJT3qlr5PFE2BZDZ6D+GspYhGCrGF6n5M6BOZc+zCgTDC9faRUbD863bV2eQqsVBUWIk4hwGgLw3BQPgQcRvdmwoSN/tIbWd0ungUrVZhzpn4x4skoMOYizDeujsBk86WZOnX0BVipAnwTXSTFYuTJvHL7rvMCjbX9Dt4JbXmmRJ4vzdRZb9+BZYlRhFF+iscsyGhoJKiF3m1owgTP9N/+yb5opm/BSlWXRv+tu0r6S3PA/F4+BUCWZ3rvWInvmhMIsFJVp6m5liejeUx+KvvxWjqKm2hz4qWZOrUJHFipYlHW2/Jk9bgB8GKh9xpaMJGH6k9GvFjNnOsUaLBS1ViMKSmWJ3NqrtN4V4M66hGYEDd1O5QNz5mOPA4IDVipGln5MQr8ZyEp61ShIR8rRRDHuF2QC4FFE4LcS9G6qC5NXVk2nOknyKZoGE6ioZQvM+FlgibWhaT0yZpo4y64ZvWTnn2m4Gx4Lit1ke/ku054I+uObge8VNgK8v7tsirauAUfloet8RO1dz7mm1ue7LwspwsfQpxkwUT5puAvdhtfQTiqN+GxGjYYQl7LYKVX4EzcTZ6OYSSaQ2ZugG4G8mYdyDN1oGvbA+9E8dJ/kGvqc+nN9V67dlIYz8qq+oaHpCHv8FrluD7+VcCQxsOypq7xCoxnvujfZnDNvaYABj6ulPX19UVHjXJaOuOPQSivqcj1GatA/zTZU2ZO3KmKNjwhhbyJNWIF8lXAEMYfOmPbbh4Gq/FqfHguX6N8PCuAO8JrOmMtfzbBqj67qRzTDj/Cx0qWhhXIQ4GEKJBnxT9s7hmZw1pd37ROGMY9eRjlQ8OugJT3xlub15EMI2DVRJdWGCK5g71W2Omw3f6EFJEle2Jbu0aBZXaJ7LVsqxr6A1O81dfAYq8VejzsCjDKW30NLPqPqmjT7UjJ/87uGfi48CmAIcGfdMaaH0ltedoFaJhKsx473RI+ibjFNhR4PB5ruXXsceOubKyONryL2H6xjRPxIaFRwNgZj207P11zJ1wyC8/l/+UuobnIzjcUnmpcfjKuxQZcm1Dly5yvNp/Rwwq8BKhWT1S/jGCZaYho011CGA94uKFcNccUkHfHY80PZjpdVmCZcNU1Xo906v34yMM+mVQN5vcJYYi18baWp7JpXtZgkTEzz2UMrRFSrmHAspE3EPskcL3XYzLoo1ZWPZtW5QSWZdAETBq3YWyRbjPZg2WjtP/2sQWU1UxbYFkHz627cGZEFFwqI3IV3OSl+P8i/+nHNU5R4CTCnZeNpPFKUgy+vK9te7dddfICK/WktbUri08U9q4W0rggYojKpJAVWCxeiXxFBfYL5bpFuxfFgeP6cOG7sJIoERFGV1IKxE/y38UDpZs6Ol5V8kqM/wNvVYi23YKH4QAAAABJRU5ErkJggg==";
+
+mod cache;
+mod cli;
+mod curseforge;
+mod dependencies;
+mod downloads;
+mod error;
+mod hashes;
+mod install;
+mod java;
+mod launcher;
+mod logging;
+mod manifest;
+mod modrinth;
+mod mods_dir;
+mod packs;
+mod resolve;
+mod settings;
+#[cfg(test)]
+mod test_support;
+
 #[derive(Copy, Clone)]
 enum Page {
     ManifestDownloadPage,
@@ -31,13 +53,24 @@ enum Page {
     Complete,
     FabricCheckPage,
     ForgeCheckPage,
-    JavaCheckPage
+    QuiltCheckPage,
+    NeoForgeCheckPage,
+    JavaCheckPage,
+    JavaDownloadPage,
+    BackupsPage,
+    ImportPage,
+    ExportPage,
+    ProfileEditor,
+    LauncherProfilesPage,
+    Error
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, PartialEq)]
 enum ModLoader {
     Fabric,
-    Forge
+    Forge,
+    Quilt,
+    NeoForge
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
@@ -51,7 +84,25 @@ struct AppState {
     page: Page,
     selected_profile: i32,
     manifest: Manifest,
-    download_list: ModDownloads
+    download_list: ModDownloads,
+    sync: mods_dir::SyncSummary,
+    // The local profile open in `Page::ProfileEditor`, None for a new one
+    editing: Option<i32>,
+    // The Java picked (or downloaded) for the selected profile before its loader is installed
+    java: Option<java::JavaInstall>,
+    // The `versions/` folder the loader check page installed, which the launcher entry starts
+    installed_version: String,
+    error: Option<AppError>
+}
+
+// What `Page::Error` shows, and where its retry and back buttons go
+#[derive(Clone)]
+struct AppError {
+    title: String,
+    message: String,
+    help_url: Option<String>,
+    retry: Page,
+    back: Option<Page>
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
@@ -65,10 +116,35 @@ enum ModProvider {
 #[derive(Serialize, Deserialize, Clone, PartialEq, Props)]
 struct Mod {
     name: String,
+    // Empty when the mod is looked up by `project` instead
+    #[serde(default)]
     url: String,
+    #[serde(default)]
     version: String,
     provider: ModProvider,
-    size: i32
+    #[serde(default)]
+    size: i32,
+    // Project id or slug on Modrinth
+    #[serde(default)]
+    project: Option<String>,
+    // Project and file ids on CurseForge
+    #[serde(default)]
+    projectID: Option<u64>,
+    #[serde(default)]
+    fileID: Option<u64>,
+    // Defaults to the last part of the url
+    #[serde(default)]
+    filename: Option<String>,
+    // The url is a page to download the file from by hand, for hosts that don't allow
+    // downloads from other apps
+    #[serde(default)]
+    manual: bool,
+    #[serde(default)]
+    sha1: Option<String>,
+    #[serde(default)]
+    sha256: Option<String>,
+    #[serde(default)]
+    sha512: Option<String>
 }
 
 #[derive(Clone, PartialEq)]
@@ -79,7 +155,14 @@ struct ModDownload {
     bytes_total: i32,
     bytes_read: i32,
     status: Download,
-    provider: ModProvider
+    provider: ModProvider,
+    filename: Option<String>,
+    manual: bool,
+    // Added because another mod needs it, rather than listed in the manifest
+    dependency: bool,
+    sha1: Option<String>,
+    sha256: Option<String>,
+    sha512: Option<String>
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -93,13 +176,22 @@ struct ProfileMeta {
     name: String,
     loader: ModLoader,
     version: String,
-    id: i32
+    id: i32,
+    // Exact loader version to install, instead of the one ModTool would pick for `version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    loader_version: Option<String>,
+    // Folder that's copied over the game directory on install, from an imported modpack
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    overrides: Option<String>
 }
 
 #[derive(Clone, PartialEq)]
 enum Download {
+    Queued,
     InProgress,
-    Complete
+    Complete,
+    Failed,
+    Cancelled
 }
 
 
@@ -113,39 +205,6 @@ struct Manifest {
     profiles: Vec<Profile>
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct LauncherProfile {
-    created: Option<String>,
-    icon: String,    // Should be base64 encoded PNG
-    javaArgs: Option<String>,
-    lastUsed: String,
-    lastVersionId: String,
-    name: String,
-    r#type: String
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-struct LauncherSettings {
-    crashAssistance: bool,
-    enableAdvanced: bool,
-    enableAnalytics: bool,
-    enableHistorical: bool,
-    enableReleases: bool,
-    enableSnapshots: bool,
-    keepLauncherOpen: bool,
-    profileSorting: String,
-    showGameLog: bool,
-    showMenu: bool,
-    soundOn: bool
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-struct LauncherProfiles {
-    profiles: HashMap<String, LauncherProfile>,
-    settings: LauncherSettings,
-    version: i32
-}
-
 struct MCData {
     base_dir: String,
     mods_dir: String,
@@ -154,13 +213,38 @@ struct MCData {
 }
 
 impl Manifest {
-    fn lookup(&self, id: i32) -> Profile {
-        for profile in self.profiles.clone() {
-            if profile.meta.id == id {
-                return profile.clone();
+    fn lookup(&self, id: i32) -> Option<Profile> {
+        self.profiles.iter().find(|profile| profile.meta.id == id).cloned()
+    }
+
+    // Matches a profile by id, or by name ignoring case
+    fn find(&self, query: &str) -> Option<Profile> {
+        if let Ok(id) = query.parse::<i32>() {
+            if let Some(profile) = self.profiles.iter().find(|profile| profile.meta.id == id) {
+                return Some(profile.clone());
             }
         }
-        return self.profiles[0].clone();
+        self.profiles.iter()
+            .find(|profile| profile.meta.name.to_lowercase() == query.to_lowercase())
+            .cloned()
+    }
+}
+
+impl ProfileMeta {
+    // Each profile gets its own game directory under CONFIG_DIR/instances, unless
+    // `isolate_profiles` is turned off in the settings
+    fn game_dir(&self) -> String {
+        match settings::settings().isolate_profiles {
+            true => Path::new(CONFIG_DIR.as_str()).join("instances").join(self.id.to_string()).display().to_string(),
+            false => MC_DATA.base_dir.clone()
+        }
+    }
+
+    fn mods_dir(&self) -> String {
+        match settings::settings().isolate_profiles {
+            true => Path::new(self.game_dir().as_str()).join("mods").display().to_string(),
+            false => MC_DATA.mods_dir.clone()
+        }
     }
 }
 
@@ -175,14 +259,20 @@ static STATE: Atom<AppState> = |_| AppState {
     },
     download_list: ModDownloads {
         downloads: Vec::new()
-    }
+    },
+    sync: mods_dir::SyncSummary {
+        added: 0,
+        updated: 0,
+        removed: 0,
+        unchanged: 0
+    },
+    editing: None,
+    java: None,
+    installed_version: String::new(),
+    error: None
 };
 
 
-static NUM_DOWNLOADS: Atom<i32> = |_| {
-    0
-};
-
 /*
 struct DownloadHandler {
     current_downloads: i32,
@@ -194,8 +284,17 @@ impl DownloadHandler {
 }
 */
 
+// Mod jars, installers and Java runtimes can take much longer than the read timeout to download.
+// Mod downloads also time out on their own when no data arrives for the read timeout.
+static FILE_DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3600);
+
 lazy_static! {
-    static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::new();
+    // The read timeout covers the whole request, which is plenty for API calls
+    static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(settings::settings().connect_timeout_secs))
+        .timeout(std::time::Duration::from_secs(settings::settings().read_timeout_secs))
+        .build()
+        .unwrap();
     static ref CACHE_DIR: String =  {
         match cfg!(windows) {
             true => env::var("APPDATA").unwrap() + "\\modtool-rs\\cache\\",
@@ -229,6 +328,14 @@ fn init_dirs() {
 
 fn main() {
     init_dirs();
+    logging::init();
+    let args = match cli::take_global_args(env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(err) => process::exit(cli::usage_error(err))
+    };
+    if !args.is_empty() {
+        process::exit(cli::run(&args));
+    }
     dioxus::desktop::launch_cfg(App, |c| {
         c.with_custom_head("<link href=\"https://tallie.dev/modtool/assets/tailwind.css\" rel=\"stylesheet\" /><style>html {background: #334155; display: flex; flex-direction: column;}</style><script src=\"https://kit.fontawesome.com/a  0e919fade.js\" crossorigin=\"anonymous\"></script><link rel=\"stylesheet\" href=\"https://cdn.jsdelivr.net/npm/@fortawesome/fontawesome-free@6.1.1/css/fontawesome.min.css\" integrity=\"sha384-zIaWifL2YFF1qaDiAo0JFgsmasocJ/rqu7LKYH8CoB  EXqGbb9eO+Xi3s6fQhgFWM\" crossorigin=\"anonymous\">".to_string())
     });
@@ -266,12 +373,39 @@ fn PageRouter(cx: Scope) -> Element {
                 Page::JavaCheckPage => {
                     rsx! { JavaCheckPage {} }
                 },
+                Page::JavaDownloadPage => {
+                    rsx! { JavaDownloadPage {} }
+                },
                 Page::ForgeCheckPage => {
                     rsx! { ForgeCheckPage {} }
                 },
                 Page::FabricCheckPage => {
                     rsx! { FabricCheckPage {} }
                 },
+                Page::QuiltCheckPage => {
+                    rsx! { QuiltCheckPage {} }
+                },
+                Page::NeoForgeCheckPage => {
+                    rsx! { NeoForgeCheckPage {} }
+                },
+                Page::BackupsPage => {
+                    rsx! { BackupsPage {} }
+                },
+                Page::ImportPage => {
+                    rsx! { ImportPage {} }
+                },
+                Page::ExportPage => {
+                    rsx! { ExportPage {} }
+                },
+                Page::ProfileEditor => {
+                    rsx! { ProfileEditor {} }
+                },
+                Page::LauncherProfilesPage => {
+                    rsx! { LauncherProfilesPage {} }
+                },
+                Page::Error => {
+                    rsx! { ErrorPage {} }
+                },
             }
         }
     })
@@ -330,8 +464,12 @@ fn FinishedPage(cx: Scope) -> Element {
 	let atoms = use_atom_root(&cx);
 
     //use_coroutine(&cx, |rx| to_manifest_page(rx, atoms.clone()));
-    let download_number = state.download_list.downloads.len().clone();
-    let profile_name = state.manifest.lookup(state.selected_profile).meta.name.clone();
+    let failed_number = state.download_list.downloads.iter()
+        .filter(|download| download.status == Download::Failed || download.status == Download::Cancelled)
+        .count();
+    let download_number = state.download_list.downloads.len() - failed_number;
+    let profile_name = selected_profile(atoms, state)?.meta.name;
+    let sync = state.sync.clone();
 
     cx.render(rsx! {
         div {
@@ -362,23 +500,53 @@ fn FinishedPage(cx: Scope) -> Element {
                     },
                     "mods."
                 },
+                p {
+                    class: "text-lg text-slate-400 mx-auto text-center",
+                    span { class: "text-emerald-400 font-bold", "{sync.added}" }, " added, ",
+                    span { class: "text-cyan-300 font-bold", "{sync.updated}" }, " updated, ",
+                    span { class: "text-orange-400 font-bold", "{sync.removed}" }, " removed, ",
+                    span { class: "text-slate-100 font-bold", "{sync.unchanged}" }, " unchanged"
+                },
+                (failed_number > 0).then(|| rsx! {
+                    p {
+                        class: "text-xl text-orange-600 mx-auto text-center font-bold",
+                        "{failed_number} mods failed or were cancelled and were not installed."
+                    }
+                }),
                 p {
                     class: "text-sm text-slate-500 italic text-center",
                     "You can safely close this application now."
                 }
             },
-			button {
-				class: "bg-green-500 hover:bg-green-700 rounded-xl justify-self-end p-6 self-center",
-				onclick: move |_| {
-					let mut state_cpy = state.clone();
-					state_cpy.page = Page::ProfilePage;
-					atoms.set(STATE.unique_id(), state_cpy);
+			div {
+				class: "flex flex-row gap-6 self-center",
+				button {
+					class: "bg-green-500 hover:bg-green-700 rounded-xl justify-self-end p-6",
+					onclick: move |_| {
+						let mut state_cpy = state.clone();
+						state_cpy.page = Page::ProfilePage;
+						atoms.set(STATE.unique_id(), state_cpy);
+					},
+					img {
+						src: "https://tallie.dev/modtool/assets/fa-home.svg",
+						height: "32",
+						width: "32",
+						class: "mx-auto fill-slate-100"
+					}
 				},
-				img {
-					src: "https://tallie.dev/modtool/assets/fa-home.svg",
-					height: "32",
-					width: "32",
-					class: "mx-auto fill-slate-100"
+				button {
+					class: "bg-slate-700 hover:bg-slate-600 rounded-xl justify-self-end p-6",
+					onclick: move |_| {
+						let mut state_cpy = state.clone();
+						state_cpy.page = Page::ExportPage;
+						atoms.set(STATE.unique_id(), state_cpy);
+					},
+					img {
+						src: "https://tallie.dev/modtool/assets/fa-file-export.svg",
+						height: "32",
+						width: "32",
+						class: "mx-auto fill-slate-100"
+					}
 				}
 			}
         }
@@ -386,306 +554,349 @@ fn FinishedPage(cx: Scope) -> Element {
 }
 
 async fn java_check(has_java: UseState<bool>, check_complete: UseState<bool>, java_version: UseState<String>) {
-    let com = "java";
-    let args = match cfg!(windows) {
-        true => &["-version"],
-        false => &["--version"]
-    };
-    let process = match cfg!(windows) {
-        true => {
-            match process::Command::new(com)
-            .args(args)
-            .output() {
-                Ok(process) => process,
-                Err(_) => {
-                    check_complete.set(true);
-                    has_java.set(false);
-                    return
-                }
-            }
+    // Probing runs every Java found, which takes a while
+    let installs = tokio::task::spawn_blocking(java::discover).await.unwrap_or_default();
+    match installs.first() {
+        Some(newest) => {
+            java_version.set(match installs.len() {
+                1 => format!("Java {}", newest.version),
+                count => format!("Java {} (and {} others)", newest.version, count - 1)
+            });
+            check_complete.set(true);
+            has_java.set(true);
         },
-        false => {
-            match process::Command::new(com)
-            .args(args)
-            .output() {
-                Ok(process) => process,
-                Err(_) => {
-                    check_complete.set(true);
-                    has_java.set(false);
-                    return
-                }
-            }
+        None => {
+            check_complete.set(true);
+            has_java.set(false);
         }
-
-    };
-    let text = String::from_utf8(process.stdout).unwrap();
-    let text_lines: Vec<&str> = text.split("\n").collect();
-    java_version.set(format!("{}", text_lines[0]));
-    check_complete.set(true);
-    has_java.set(true);
+    }
 }
 
-async fn forge_install(mc_version: String, found_forge: UseState<bool>, check_complete: UseState<bool>, forge_ver: UseState<String>) {
-    let manifest_res = HTTP_CLIENT
-        .get("https://tallie.dev/modtool/forge_versions.json")
-        .send()
-        .await.unwrap()
-        .text()
-        .await.unwrap();
-    let forge_manifest: Vec<ForgeVersion> = serde_json::from_str(manifest_res.clone().as_str()).unwrap();
-    let mut forge_version: String = String::from("");
-    for version in forge_manifest {
-        if version.minecraft == mc_version {
-            forge_version = version.forge;
-        }
+// Picks the Java for the profile, downloading one when nothing installed is new enough
+async fn java_select(ar: Rc<AtomRoot>, meta: ProfileMeta, check_complete: UseState<bool>, downloading: UseState<bool>) {
+    let required = java::required_major(meta.version.as_str());
+    let found = tokio::task::spawn_blocking(move || java::find_for(&meta)).await
+        .unwrap_or(Err(error::ModtoolError::JavaNotFound));
+    let res = match found {
+        Err(error::ModtoolError::JavaNotFound) | Err(error::ModtoolError::JavaTooOld { .. }) => {
+            downloading.set(true);
+            java::install_runtime(required).await
+        },
+        found => found
+    };
+    match res {
+        Ok(install) => {
+            let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+            state_cpy.java = Some(install);
+            ar.set(STATE.unique_id(), state_cpy);
+            check_complete.set(true);
+        },
+        Err(err) => show_error(&ar, err, Page::JavaDownloadPage, Some(Page::ProfilePage))
     }
+}
 
-    let profiles_dir = MC_DATA.profiles_dir.clone();
-    let current_installs = std::fs::read_dir(profiles_dir).unwrap();
-    let mut found = false;
-    for version in current_installs {
-        if format!("{}-forge-{}", mc_version, forge_version) == version.unwrap().file_name().into_string().unwrap() {
-            found = true;
-        }
-    }
-    if found {
-        for file in std::fs::read_dir(MC_DATA.mods_dir.clone()).unwrap() {
-            let file = file.unwrap();
-            match std::fs::remove_file(file.path()) {
-                Ok(_) => {},
-                Err(_) => {}
+async fn forge_install(ar: Rc<AtomRoot>, meta: ProfileMeta, java: Option<java::JavaInstall>, check_complete: UseState<bool>, forge_ver: UseState<String>, chosen: UseState<String>) {
+    let res = match install::forge_version(&meta).await {
+        Ok((version, source)) => {
+            chosen.set(format!("{}, {}", version, source));
+            match java.as_ref() {
+                Some(java) => install::install_forge(meta.version.clone(), version, java).await,
+                None => Err(error::ModtoolError::JavaNotFound)
             }
-        }
-        found_forge.set(true);
-        check_complete.set(true);
-        forge_ver.set(format!("{}-forge-{}", mc_version, forge_version));
-        return
+        },
+        Err(err) => Err(err)
+    };
+    match res {
+        Ok(version) => {
+            set_installed_version(&ar, version.clone());
+            check_complete.set(true);
+            forge_ver.set(version);
+            check_complete.needs_update();
+        },
+        Err(err) => show_error(&ar, err, Page::ForgeCheckPage, Some(Page::ProfilePage))
     }
+}
 
-    let installer_url = format!("https://maven.minecraftforge.net/net/minecraftforge/forge/{0}-{1}/forge-{0}-{1}-installer.jar", mc_version, forge_version);
-    let res = HTTP_CLIENT
-        .get(installer_url.clone())
-        .header("User-Agent", format!("Starkiller645/modtool_rs/{APP_VERSION} (tallie@tallie.dev)"))
-        .send()
-        .await.unwrap();
-
-    let url = format!("{}", installer_url.clone());
-    let path = Path::new(&url);
-    let filename = path.file_name().unwrap();
-    let filepath = format!("{}{}", CACHE_DIR.as_str(), filename.to_str().unwrap());
-    let mut fhandle = File::create(filepath.clone()).unwrap();
-    let mut content = Cursor::new(res.bytes().await.unwrap());
-    std::io::copy(&mut content, &mut fhandle).unwrap();
-
-    let com = "java";
-    let args = &["-jar", filepath.as_str()];
-    #[cfg(target_os = "windows")]
-    {
-        let _com = process::Command::new(com)
-            .args(args)
-            .creation_flags(0x08000000)
-            .output().unwrap();
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        let _com = process::Command::new(com)
-            .args(args)
-            .output().unwrap();
-    }    
-    let profiles_dir = MC_DATA.profiles_dir.clone();
-    let current_installs = std::fs::read_dir(profiles_dir).unwrap();
-    let mut found = false;
-    for version in current_installs {
-        if version.unwrap().file_name().into_string().unwrap().contains(format!("{}-forge", mc_version).as_str()) {
-            found = true;
-        }
+async fn fabric_install(ar: Rc<AtomRoot>, meta: ProfileMeta, check_complete: UseState<bool>, fabric_version: UseState<String>) {
+    match install::install_fabric(&meta).await {
+        Ok(version) => {
+            set_installed_version(&ar, version.clone());
+            check_complete.set(true);
+            fabric_version.set(version);
+            check_complete.needs_update();
+        },
+        Err(err) => show_error(&ar, err, Page::FabricCheckPage, Some(Page::ProfilePage))
     }
+}
 
+async fn quilt_install(ar: Rc<AtomRoot>, meta: ProfileMeta, check_complete: UseState<bool>, quilt_version: UseState<String>) {
+    match install::install_quilt(&meta).await {
+        Ok(version) => {
+            set_installed_version(&ar, version.clone());
+            check_complete.set(true);
+            quilt_version.set(version);
+            check_complete.needs_update();
+        },
+        Err(err) => show_error(&ar, err, Page::QuiltCheckPage, Some(Page::ProfilePage))
+    }
+}
 
-    if found {
-        for file in std::fs::read_dir(MC_DATA.mods_dir.clone()).unwrap() {
-            let file = file.unwrap();
-            match std::fs::remove_file(file.path()) {
-                Ok(_) => {},
-                Err(_) => {}
+async fn neoforge_install(ar: Rc<AtomRoot>, meta: ProfileMeta, java: Option<java::JavaInstall>, check_complete: UseState<bool>, neoforge_ver: UseState<String>, chosen: UseState<String>) {
+    let res = match install::neoforge_version(&meta).await {
+        Ok((version, source)) => {
+            chosen.set(format!("{}, {}", version, source));
+            match java.as_ref() {
+                Some(java) => install::install_neoforge(version, java).await,
+                None => Err(error::ModtoolError::JavaNotFound)
             }
-        }
-        found_forge.set(true);
-        check_complete.set(true);
-        forge_ver.set(format!("{}-forge-{}", mc_version, forge_version));
-        found_forge.needs_update();
-        check_complete.needs_update();
-    } else {
-        found_forge.set(false);
-        check_complete.set(true);
-        found_forge.needs_update();
-        check_complete.needs_update();
+        },
+        Err(err) => Err(err)
+    };
+    match res {
+        Ok(version) => {
+            set_installed_version(&ar, version.clone());
+            check_complete.set(true);
+            neoforge_ver.set(version);
+            check_complete.needs_update();
+        },
+        Err(err) => show_error(&ar, err, Page::NeoForgeCheckPage, Some(Page::ProfilePage))
     }
 }
 
-async fn fabric_install(mc_version: String, found_fabric: UseState<bool>, check_complete: UseState<bool>, fabric_version: UseState<String>) {
-   let profiles_dir = MC_DATA.profiles_dir.clone();
-    let current_installs = std::fs::read_dir(profiles_dir).unwrap();
-    let mut found = false;
-    let mut ver = String::from("");
-    for version in current_installs {
-        let fname = version.unwrap().file_name().into_string().unwrap();
-        if fname.contains("fabric-loader") && fname.contains(mc_version.as_str()) {
-            found = true;
-            let info_arr = fname.split("-").collect::<Vec<&str>>();
-            if info_arr.len() > 2 {
-                ver = String::from(info_arr[2]);
-            } else {
-                ver = String::from("unknown");
-            }
-        }
+fn set_installed_version(ar: &AtomRoot, version: String) {
+    let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+    state_cpy.installed_version = version;
+    ar.set(STATE.unique_id(), state_cpy);
+}
+
+fn show_error(ar: &AtomRoot, err: error::ModtoolError, retry: Page, back: Option<Page>) {
+    let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+    state_cpy.error = Some(AppError {
+        title: String::from(err.title()),
+        message: err.to_string(),
+        help_url: err.help_url().map(String::from),
+        retry,
+        back
+    });
+    state_cpy.page = Page::Error;
+    ar.set(STATE.unique_id(), state_cpy);
+}
+
+// Error for a profile that's no longer in the manifest, e.g. after a refresh removed it
+fn missing_profile(id: i32) -> error::ModtoolError {
+    error::ModtoolError::Manifest(format!("There's no profile with id {} in the manifest", id))
+}
+
+// The selected profile, or the error page if the manifest no longer has it
+fn selected_profile(ar: &AtomRoot, state: &AppState) -> Option<Profile> {
+    let profile = state.manifest.lookup(state.selected_profile);
+    if profile.is_none() {
+        show_error(ar, missing_profile(state.selected_profile), Page::HomePage, Some(Page::HomePage));
     }
-    if found {
-        for file in std::fs::read_dir(MC_DATA.mods_dir.clone()).unwrap() {
-            let file = file.unwrap();
-            match std::fs::remove_file(file.path()) {
-                Ok(_) => {},
-                Err(_) => {}
+    profile
+}
+
+fn ErrorPage(cx: Scope) -> Element {
+    let state = use_read(&cx, STATE);
+    let atoms = use_atom_root(&cx);
+
+    let error = match state.error.clone() {
+        Some(error) => error,
+        None => return cx.render(rsx! {""})
+    };
+    let retry = error.retry;
+
+    cx.render(rsx! {
+        div {
+            id: "errorpage",
+            class: "flex-1 flex-col flex justify-center w-full",
+            h2 {
+                class: "text-6xl text-slate-100 mx-auto text-center font-bold",
+                "Something went wrong"
+            },
+            div {
+                class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
+                div {
+                    class: "flex flex-col",
+                    p {
+                        class: "text-xl text-orange-600 font-bold text-center",
+                        "{error.title}"
+                    },
+                    p {
+                        class: "text-xl text-slate-100 text-center break-words",
+                        "{error.message}"
+                    },
+                    error.help_url.clone().map(|url| rsx! {
+                        p {
+                            class: "text-sm text-slate-500 italic text-center",
+                            "You can also do this step manually, see ",
+                            a {
+                                class: "text-sm text-sky-500 italic underline",
+                                href: "{url}",
+                                "{url}"
+                            }
+                        }
+                    }),
+                    div {
+                        class: "flex flex-row justify-center",
+                        match error.back {
+                            Some(back) => rsx! {
+                                button {
+                                    class: "bg-red-500 hover:bg-red-700 rounded-xl p-6 m-6 mb-0 align-center",
+                                    onclick: move |_| {
+                                        let mut state_cpy = state.clone();
+                                        state_cpy.error = None;
+                                        state_cpy.page = back;
+                                        atoms.set(STATE.unique_id(), state_cpy);
+                                    },
+                                    img {
+                                        src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
+                                        height: "32",
+                                        width: "32",
+                                        class: "mx-auto fill-slate-100"
+                                    }
+                                }
+                            },
+                            None => rsx! {
+                                button {
+                                    class: "bg-red-500 hover:bg-red-700 rounded-xl p-6 m-6 mb-0 align-center",
+                                    onclick: move |_| {
+                                        std::process::exit(1);
+                                    },
+                                    img {
+                                        src: "https://tallie.dev/modtool/assets/fa-xmark-circle.svg",
+                                        height: "32",
+                                        width: "32",
+                                        class: "mx-auto fill-slate-100"
+                                    }
+                                }
+                            }
+                        },
+                        button {
+                            class: "bg-green-500 hover:bg-green-700 rounded-xl p-6 m-6 mb-0 align-center",
+                            onclick: move |_| {
+                                let mut state_cpy = state.clone();
+                                state_cpy.error = None;
+                                state_cpy.page = retry;
+                                atoms.set(STATE.unique_id(), state_cpy);
+                            },
+                            img {
+                                src: "https://tallie.dev/modtool/assets/fa-rotate-right.svg",
+                                height: "32",
+                                width: "32",
+                                class: "mx-auto fill-slate-100"
+                            }
+                        }
+                    }
+                }
             }
         }
-        found_fabric.set(true);
-        check_complete.set(true);
-        fabric_version.set(format!("Fabric {} for Minecraft {}", ver, mc_version));
-        return
-    }
+    })
+}
 
-    let installer_url = format!("https://maven.fabricmc.net/net/fabricmc/fabric-installer/0.11.0/fabric-installer-0.11.0.jar");
-    let res = HTTP_CLIENT
-        .get(installer_url.clone())
-        .header("User-Agent", format!("Starkiller645/modtool_rs/{APP_VERSION} (tallie@tallie.dev)"))
-        .send()
-        .await.unwrap();
-
-    let url = format!("{}", installer_url.clone());
-    let path = Path::new(&url);
-    let filename = path.file_name().unwrap();
-    let filepath = format!("{}{}", CACHE_DIR.as_str(), filename.to_str().unwrap());
-    let mut fhandle = File::create(filepath.clone()).unwrap();
-    let mut content = Cursor::new(res.bytes().await.unwrap());
-    std::io::copy(&mut content, &mut fhandle).unwrap();
-
-    let com = "java";
-    let args = &["-jar", filepath.as_str(), "client", "-mcversion", mc_version.as_str(), "-dir", MC_DATA.base_dir.as_str()];
-
-    #[cfg(target_os = "windows")]
-    {
-        let _com = process::Command::new(com)
-            .args(args)
-            .creation_flags(0x08000000)
-            .output().unwrap();
+fn loader_check_page(loader: ModLoader) -> Page {
+    match loader {
+        ModLoader::Forge => Page::ForgeCheckPage,
+        ModLoader::Fabric => Page::FabricCheckPage,
+        ModLoader::Quilt => Page::QuiltCheckPage,
+        ModLoader::NeoForge => Page::NeoForgeCheckPage
     }
+}
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        let _com = process::Command::new(com)
-            .args(args)
-            .output().unwrap();
-    }
+fn JavaDownloadPage(cx: Scope) -> Element {
+    let check_complete = use_state(&cx, || false);
+    // Nothing installed was new enough
+    let downloading = use_state(&cx, || false);
 
-    let profiles_dir = MC_DATA.profiles_dir.clone();
-    let current_installs = std::fs::read_dir(profiles_dir).unwrap();
-    let mut found = false;
-    for version in current_installs {
-        let fname = version.unwrap().file_name().into_string().unwrap();
-        if fname.contains("fabric-loader") && fname.contains(mc_version.as_str()) {
-            found = true;
-        }
-    }
+    let atoms = use_atom_root(&cx);
+    let state = use_read(&cx, STATE);
 
+    let meta = selected_profile(atoms, state)?.meta;
+    let major = java::runtime_major(java::required_major(meta.version.as_str()));
+    let mc_version = meta.version.clone();
+    let loader = meta.loader;
 
-    if found {
-        for file in std::fs::read_dir(MC_DATA.mods_dir.clone()).unwrap() {
-            let file = file.unwrap();
-            match std::fs::remove_file(file.path()) {
-                Ok(_) => {},
-                Err(_) => {}
+    use_future(&cx, (), |_| java_select(atoms.clone(), meta, check_complete.clone(), downloading.clone()));
+
+    cx.render(rsx! {
+        div {
+            id: "javadownloadpage",
+            class: "flex-1 flex-col flex justify-center w-full",
+            match *check_complete.current() {
+                true => {
+                    let mut state_cpy = state.clone();
+                    state_cpy.page = loader_check_page(loader);
+                    atoms.set(STATE.unique_id(), state_cpy);
+                    rsx! {""}
+                },
+                false if !*downloading.current() => {
+                    rsx! {
+                        h2 {
+                            class: "text-6xl text-slate-100 mx-auto text-center font-bold p-6",
+                            "Checking for Java..."
+                        },
+                        div {
+                            class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
+                            p {
+                                class: "text-xl text-slate-100 font-bold text-center",
+                                "Looking for a Java that can run Minecraft {mc_version}."
+                            },
+                            img {
+                                src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
+                                class: "mx-auto pt-6 w-1/2",
+                            }
+                        }
+                    }
+                },
+                false => {
+                    rsx! {
+                        h2 {
+                            class: "text-6xl text-slate-100 mx-auto text-center font-bold p-6",
+                            "Downloading Java..."
+                        },
+                        div {
+                            class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
+                            p {
+                                class: "text-xl text-slate-100 font-bold text-center",
+                                "Minecraft {mc_version} needs Java {major}, which is now downloading."
+                            },
+                            p {
+                                class: "text-sm text-slate-500 italic text-center",
+                                "Temurin Java is downloaded from Adoptium into ModTool's own folder, and only used for ModTool profiles."
+                            }
+                            img {
+                                src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
+                                class: "mx-auto pt-6 w-1/2",
+                            }
+                        }
+                    }
+                }
             }
         }
-        found_fabric.set(true);
-        check_complete.set(true);
-        fabric_version.set(format!("Fabric {} for Minecraft {}", ver, mc_version));
-        found_fabric.needs_update();
-        check_complete.needs_update();
-    } else {
-        found_fabric.set(false);
-        check_complete.set(true);
-        found_fabric.needs_update();
-        check_complete.needs_update();
-    }
+    })
 }
 
 fn FabricCheckPage(cx: Scope) -> Element {
     let check_complete = use_state(&cx, || false);
-    let has_fabric = use_state(&cx, || false);
     let fabric_version = use_state(&cx, || String::from(""));
 
     let atoms = use_atom_root(&cx);
     let state = use_read(&cx, STATE);
 
-    let mc_version = state.manifest.lookup(state.selected_profile).clone().meta.version;
+    let meta = selected_profile(atoms, state)?.meta;
 
-    use_future(&cx, (), |_| fabric_install(mc_version, has_fabric.clone(), check_complete.clone(), fabric_version.clone()));
+    use_future(&cx, (), |_| fabric_install(atoms.clone(), meta, check_complete.clone(), fabric_version.clone()));
 
     cx.render(rsx! {
         div {
             id: "fabriccheckpage",
             class: "flex-1 flex-col flex justify-center w-full",
             match *check_complete.current() {
-                true => rsx! {
-                    div {
-                        class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
-                        match *has_fabric.current() {
-                            true => {
-                                let mut state_cpy = state.clone();
-                                state_cpy.page = Page::DownloadPage;
-                                atoms.set(STATE.unique_id(), state_cpy);
-                                rsx! {""}
-                            },
-                            false => {
-                                rsx! {
-                                    div {
-                                        class: "flex flex-col",
-                                        p {
-                                            class: "text-xl text-orange-600 font-bold text-center",
-                                            "Could not install Fabric!"
-                                        },
-                                        p {
-                                            class: "text-xl text-slate-100 text-center",
-                                            "Try re-running the program, or download and install Fabric manually."
-                                        },
-                                        p {
-                                            class: "text-sm text-slate-500 italic text-center",
-                                            "Fabric Mod Loader can be downloaded from ",
-                                            a {
-                                                class: "text-sm text-sky-500 italic underline",
-                                                href: "https://fabricmc.net/use/installer/",
-                                                "https://fabricmc.net/use/installer/"
-                                            }
-                                        },
-                                        button {
-                                            class: "bg-red-500 hover:bg-red-700 rounded-xl p-6 m-6 mb-0 mx-auto align-center",
-                                            onclick: move |_| {
-                                                let mut state_cpy = state.clone();
-                                                state_cpy.page = Page::ProfilePage;
-                                                atoms.set(STATE.unique_id(), state_cpy);
-                                            },
-                                            img {
-                                                src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
-                                                height: "32",
-                                                width: "32",
-                                                class: "mx-auto fill-slate-100"
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                true => {
+                    let mut state_cpy = state.clone();
+                    state_cpy.page = Page::DownloadPage;
+                    atoms.set(STATE.unique_id(), state_cpy);
+                    rsx! {""}
                 },
                 false => {
                     rsx! {
@@ -701,7 +912,7 @@ fn FabricCheckPage(cx: Scope) -> Element {
                             },
                             p {
                                 class: "text-sm text-slate-500 italic text-center",
-                                "Fabric Mod Loader will install automatically. Please wait for the install to finish."
+                                "Fabric Mod Loader is downloaded from Fabric Meta. Please wait for the install to finish."
                             }
                             img {
                                 src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
@@ -717,17 +928,18 @@ fn FabricCheckPage(cx: Scope) -> Element {
 
 fn ForgeCheckPage(cx: Scope) -> Element {
     let check_complete = use_state(&cx, || false);
-    let has_forge = use_state(&cx, || false);
     let forge_version = use_state(&cx, || String::from(""));
+    // The build that's being installed, and why that one
+    let chosen = use_state(&cx, || String::from(""));
 
     let atoms = use_atom_root(&cx);
     let state = use_read(&cx, STATE);
 
-    let mc_version = state.manifest.lookup(state.selected_profile).clone().meta.version;
+    let meta = selected_profile(atoms, state)?.meta;
 
-    use_future(&cx, (), |_| forge_install(mc_version, has_forge.clone(), check_complete.clone(), forge_version.clone()));
+    use_future(&cx, (), |_| forge_install(atoms.clone(), meta, state.java.clone(), check_complete.clone(), forge_version.clone(), chosen.clone()));
 
-/*    if *check_complete.get() && *has_forge.get() {
+/*    if *check_complete.get() {
         let mut state_cpy = state.clone();
         state_cpy.page = Page::DownloadPage;
         atoms.set(STATE.unique_id(), state_cpy);
@@ -745,68 +957,32 @@ fn ForgeCheckPage(cx: Scope) -> Element {
                     },
                     div {
                         class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
-                        match *has_forge.get() {
-                            true => rsx! {
-                                div {
-                                    class: "flex flex-col",
-                                    p {
-                                        class: "text-xl text-slate-300 mx-auto text-center",
-                                        "Found Forge: "
-                                    },
-                                    p {
-                                        class: "text-orange-600 font-bold text-xl text-center",
-                                        "{forge_version}"
-                                    },
-                                    button {
-                                        class: "bg-green-500 hover:bg-green-700 rounded-xl m-6 p-6 align-center mx-auto",
-                                        onclick: move |_| {
-                                            let mut state_cpy = state.clone();
-                                            state_cpy.page = Page::DownloadPage;
-                                            atoms.set(STATE.unique_id(), state_cpy);
-                                        },
-                                        img {
-                                            src: "https://tallie.dev/modtool/assets/fa-arrow-right.svg",
-                                            height: "32",
-                                            width: "32",
-                                            class: "mx-auto fill-slate-100"
-                                        }
-                                    }
-                                }
+                        div {
+                            class: "flex flex-col",
+                            p {
+                                class: "text-xl text-slate-300 mx-auto text-center",
+                                "Found Forge: "
                             },
-                            false => rsx! {
-                                div {
-                                    class: "flex flex-col",
-                                    p {
-                                        class: "text-xl text-orange-600 font-bold text-center",
-                                        "Could not install Forge!"
-                                    },
-                                    p {
-                                        class: "text-xl text-slate-100 text-center",
-                                        "Try re-running the program, or download and install Forge manually."
-                                    },
-                                    p {
-                                        class: "text-sm text-slate-500 italic text-center",
-                                        "Forge Mod Loader can be downloaded from ",
-                                        a {
-                                            class: "text-sm text-sky-500 italic underline",
-                                            href: "https://files.minecraftforge.net/",
-                                            "https://files.minecraftforge.net/"
-                                        }
-                                    },
-                                    button {
-                                        class: "bg-red-500 hover:bg-red-700 rounded-xl p-6 m-6 mb-0 mx-auto align-center",
-                                        onclick: move |_| {
-                                            let mut state_cpy = state.clone();
-                                            state_cpy.page = Page::ProfilePage;
-                                            atoms.set(STATE.unique_id(), state_cpy);
-                                        },
-                                        img {
-                                            src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
-                                            height: "32",
-                                            width: "32",
-                                            class: "mx-auto fill-slate-100"
-                                        }
-                                    }
+                            p {
+                                class: "text-orange-600 font-bold text-xl text-center",
+                                "{forge_version}"
+                            },
+                            p {
+                                class: "text-sm text-slate-500 italic text-center",
+                                "Forge {chosen}"
+                            },
+                            button {
+                                class: "bg-green-500 hover:bg-green-700 rounded-xl m-6 p-6 align-center mx-auto",
+                                onclick: move |_| {
+                                    let mut state_cpy = state.clone();
+                                    state_cpy.page = Page::DownloadPage;
+                                    atoms.set(STATE.unique_id(), state_cpy);
+                                },
+                                img {
+                                    src: "https://tallie.dev/modtool/assets/fa-arrow-right.svg",
+                                    height: "32",
+                                    width: "32",
+                                    class: "mx-auto fill-slate-100"
                                 }
                             }
                         }
@@ -822,15 +998,166 @@ fn ForgeCheckPage(cx: Scope) -> Element {
                             class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
                             p {
                                 class: "text-xl text-slate-100 font-bold text-center",
-                                "Forge is now downloading."
+                                "Forge is now installing."
+                            },
+                            (!chosen.is_empty()).then(|| rsx! {
+                                p {
+                                    class: "text-xl text-orange-600 font-bold text-center",
+                                    "Forge {chosen}"
+                                }
+                            }),
+                            p {
+                                class: "text-xl text-slate-100 text-center",
+                                "The installer runs in the background and can take a few minutes."
+                            },
+                            p {
+                                class: "text-sm text-slate-500 italic text-center",
+                                "Older installers open a window instead. If one appears, select ⦿ Install client, click [OK], wait for the install to complete, then click [OK] to finish."
+                            }
+                            img {
+                                src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
+                                class: "mx-auto pt-6 w-1/2",
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+
+}
+
+fn QuiltCheckPage(cx: Scope) -> Element {
+    let check_complete = use_state(&cx, || false);
+    let quilt_version = use_state(&cx, || String::from(""));
+
+    let atoms = use_atom_root(&cx);
+    let state = use_read(&cx, STATE);
+
+    let meta = selected_profile(atoms, state)?.meta;
+
+    use_future(&cx, (), |_| quilt_install(atoms.clone(), meta, check_complete.clone(), quilt_version.clone()));
+
+    cx.render(rsx! {
+        div {
+            id: "quiltcheckpage",
+            class: "flex-1 flex-col flex justify-center w-full",
+            match *check_complete.current() {
+                true => {
+                    let mut state_cpy = state.clone();
+                    state_cpy.page = Page::DownloadPage;
+                    atoms.set(STATE.unique_id(), state_cpy);
+                    rsx! {""}
+                },
+                false => {
+                    rsx! {
+                        h2 {
+                            class: "text-6xl text-slate-100 mx-auto text-center font-bold p-6",
+                            "Installing Quilt..."
+                        },
+                        div {
+                            class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
+                            p {
+                                class: "text-xl text-slate-100 font-bold text-center",
+                                "Quilt is now installing."
+                            },
+                            p {
+                                class: "text-sm text-slate-500 italic text-center",
+                                "Quilt Loader is downloaded from Quilt Meta. Please wait for the install to finish."
+                            }
+                            img {
+                                src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
+                                class: "mx-auto pt-6 w-1/2",
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn NeoForgeCheckPage(cx: Scope) -> Element {
+    let check_complete = use_state(&cx, || false);
+    let neoforge_version = use_state(&cx, || String::from(""));
+    // The build that's being installed, and why that one
+    let chosen = use_state(&cx, || String::from(""));
+
+    let atoms = use_atom_root(&cx);
+    let state = use_read(&cx, STATE);
+
+    let meta = selected_profile(atoms, state)?.meta;
+
+    use_future(&cx, (), |_| neoforge_install(atoms.clone(), meta, state.java.clone(), check_complete.clone(), neoforge_version.clone(), chosen.clone()));
+
+    cx.render(rsx! {
+        div {
+            id: "neoforgecheckpage",
+            class: "flex-1 flex-col flex justify-center w-full",
+            match *check_complete.current() {
+                true => { rsx! { 
+                    h2 {
+                        class: "text-6xl text-slate-100 mx-auto text-center font-bold",
+                        "Check complete!"
+                    },
+                    div {
+                        class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
+                        div {
+                            class: "flex flex-col",
+                            p {
+                                class: "text-xl text-slate-300 mx-auto text-center",
+                                "Found NeoForge: "
+                            },
+                            p {
+                                class: "text-orange-600 font-bold text-xl text-center",
+                                "{neoforge_version}"
+                            },
+                            p {
+                                class: "text-sm text-slate-500 italic text-center",
+                                "NeoForge {chosen}"
+                            },
+                            button {
+                                class: "bg-green-500 hover:bg-green-700 rounded-xl m-6 p-6 align-center mx-auto",
+                                onclick: move |_| {
+                                    let mut state_cpy = state.clone();
+                                    state_cpy.page = Page::DownloadPage;
+                                    atoms.set(STATE.unique_id(), state_cpy);
+                                },
+                                img {
+                                    src: "https://tallie.dev/modtool/assets/fa-arrow-right.svg",
+                                    height: "32",
+                                    width: "32",
+                                    class: "mx-auto fill-slate-100"
+                                }
+                            }
+                        }
+                    }
+                }},
+                false => {
+                    rsx! {
+                        h2 {
+                            class: "text-6xl text-slate-100 mx-auto text-center font-bold p-6",
+                            "Installing NeoForge..."
+                        },
+                        div {
+                            class: "bg-slate-900 rounded-xl p-6 m-6 mx-auto flex-col w-1/2",
+                            p {
+                                class: "text-xl text-slate-100 font-bold text-center",
+                                "NeoForge is now installing."
                             },
+                            (!chosen.is_empty()).then(|| rsx! {
+                                p {
+                                    class: "text-xl text-orange-600 font-bold text-center",
+                                    "NeoForge {chosen}"
+                                }
+                            }),
                             p {
                                 class: "text-xl text-slate-100 text-center",
-                                "When the installer window appears, please install Forge normally."
+                                "The installer runs in the background and can take a few minutes."
                             },
                             p {
                                 class: "text-sm text-slate-500 italic text-center",
-                                "Select ⦿ Install client, click [OK], wait for the install to complete, then click [OK] to finish."
+                                "Older installers open a window instead. If one appears, select ⦿ Install client, click [OK], wait for the install to complete, then click [OK] to finish."
                             }
                             img {
                                 src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
@@ -910,7 +1237,7 @@ fn JavaCheckPage(cx: Scope) -> Element {
                                     },
                                     p {
                                         class: "text-xl text-slate-100 text-center",
-                                        "Please download and install Java, then re-run this application."
+                                        "ModTool will download Java for you when you install a profile."
                                     },
                                     p {
                                         class: "text-sm text-slate-500 italic text-center",
@@ -922,12 +1249,14 @@ fn JavaCheckPage(cx: Scope) -> Element {
                                         }
                                     },
                                     button {
-                                        class: "bg-red-500 hover:bg-red-700 rounded-xl p-6 m-6 mb-0 mx-auto align-center",
+                                        class: "bg-green-500 hover:bg-green-700 rounded-xl p-6 m-6 mb-0 mx-auto align-center",
                                         onclick: move |_| {
-                                            std::process::exit(1);
+                                            let mut state_cpy = state.clone();
+                                            state_cpy.page = Page::ProfilePage;
+                                            atoms.set(STATE.unique_id(), state_cpy);
                                         },
                                         img {
-                                            src: "https://tallie.dev/modtool/assets/fa-xmark-circle.svg",
+                                            src: "https://tallie.dev/modtool/assets/fa-arrow-right.svg",
                                             height: "32",
                                             width: "32",
                                             class: "mx-auto fill-slate-100"
@@ -967,8 +1296,6 @@ fn ProfileInfo(cx: Scope, meta: ProfileMeta) -> Element {
     let mut text_secondary_color = "text-slate-300";
     let mut bg_primary_color = "bg-slate-700";
     let mut bg_secondary_color = "hover:bg-slate-600";
-    let mut loader_accent_color = "text-sky-500";
-    let mut loader_name = "Fabric";
 
     if meta.id == state.selected_profile {
         text_primary_color = "text-slate-900";
@@ -977,14 +1304,15 @@ fn ProfileInfo(cx: Scope, meta: ProfileMeta) -> Element {
         bg_secondary_color = "hover:bg-slate-300";
     }
 
-    if meta.loader == ModLoader::Forge {
-        loader_accent_color = "text-orange-300";
-        loader_name = "Forge";
-    }
+    let (loader_name, loader_accent_color) = match meta.loader {
+        ModLoader::Fabric => ("Fabric", "text-sky-500"),
+        ModLoader::Forge => ("Forge", "text-orange-300"),
+        ModLoader::Quilt => ("Quilt", "text-purple-400"),
+        ModLoader::NeoForge => ("NeoForge", "text-amber-500")
+    };
 
     let mods_txt;
-    let profiles_list = state.current().manifest.profiles.clone();
-    let this_profile = profiles_list.clone()[meta.id as usize].clone();
+    let this_profile = state.current().manifest.lookup(meta.id)?;
     if this_profile.mods.len() == 0 {
         mods_txt = String::from("No mods.");
     } else if this_profile.mods.len() == 1 {
@@ -995,7 +1323,9 @@ fn ProfileInfo(cx: Scope, meta: ProfileMeta) -> Element {
 
     let icon_url = match meta.loader {
         ModLoader::Fabric => "https://tallie.dev/modtool/assets/fa-scroll.svg",
-        ModLoader::Forge => "https://tallie.dev/modtool/assets/fa-hammer.svg"
+        ModLoader::Forge => "https://tallie.dev/modtool/assets/fa-hammer.svg",
+        ModLoader::Quilt => "https://tallie.dev/modtool/assets/fa-layer-group.svg",
+        ModLoader::NeoForge => "https://tallie.dev/modtool/assets/fa-fire.svg"
     };
 
     cx.render(rsx! {
@@ -1015,7 +1345,13 @@ fn ProfileInfo(cx: Scope, meta: ProfileMeta) -> Element {
                     class: "flex-1 flex flex-col",
                     h3 {
                         class: "text-3xl font-bold {text_primary_color}",
-                        "{meta.name}"
+                        "{meta.name}",
+                        (manifest::is_local(meta.id)).then(|| rsx! {
+                            span {
+                                class: "text-base text-emerald-500 ml-3 align-middle",
+                                "LOCAL"
+                            }
+                        })
                     },
                     p {
                         class: "text-xl {text_secondary_color}",
@@ -1040,102 +1376,164 @@ fn ProfileInfo(cx: Scope, meta: ProfileMeta) -> Element {
     })
 }
 
-fn DownloadPage(cx: Scope) -> Element {
-    let ar = use_atom_root(&cx);
-    let mut state = (*ar.read(STATE)).clone();
-
-    let current_profile = state.manifest.lookup(state.selected_profile).clone();
-    let mut mod_info_with_status = Vec::new();
-
-   for modinfo in current_profile.mods.iter() {
-        let modinfo = modinfo.clone();
-        mod_info_with_status.push(ModDownload {
-            name: modinfo.name,
-            url: modinfo.url,
-            version: modinfo.version,
-            bytes_total: 0,
-            bytes_read: 0,
-            status: Download::InProgress,
-            provider: modinfo.provider
-        });
+// Looks up the selected profile's mods, syncs its mods folder and queues everything that isn't
+// installed yet. The resolved profile replaces the one in the manifest, so later pages see the
+// files that were actually installed.
+async fn prepare_downloads(ar: &AtomRoot, manager: &downloads::DownloadManager) -> error::Result<()> {
+    let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+    state_cpy.download_list = ModDownloads {
+        downloads: Vec::new()
     };
+    ar.set(STATE.unique_id(), state_cpy.clone());
+
+    let profile = state_cpy.manifest.lookup(state_cpy.selected_profile).ok_or_else(|| missing_profile(state_cpy.selected_profile))?;
+    let profile = resolve::resolve_profile(profile).await?;
+    let mods_dir = profile.meta.mods_dir();
+    install::create_game_dir(&profile.meta)?;
+    let plan = mods_dir::sync_mods_dir(&profile, mods_dir.as_str())?;
+    install::apply_overrides(&profile.meta)?;
+
+    // Mods that are already installed start out complete
+    let mut downloads = install::mod_downloads(&profile);
+    for download in downloads.iter_mut() {
+        if plan.unchanged.contains(&download.url) {
+            download.status = Download::Complete;
+        }
+    }
 
-    state.download_list = ModDownloads {
-        downloads: mod_info_with_status.clone()
+    let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+    for existing in state_cpy.manifest.profiles.iter_mut() {
+        if existing.meta.id == profile.meta.id {
+            *existing = profile.clone();
+        }
+    }
+    state_cpy.download_list = ModDownloads {
+        downloads: downloads.clone()
     };
-    ar.set(STATE.unique_id(), state.clone());
-
-    use_future(&cx, (), |_| { 
-        let state = state.clone();
-        async move {
-            let current_profile = state.manifest.lookup(state.selected_profile).clone();
+    state_cpy.sync = plan.summary;
+    ar.set(STATE.unique_id(), state_cpy);
 
-            let profiles_file = MC_DATA.base_dir.clone() + "launcher_profiles.json";
-            let mut data = String::new(); 
-			{
-	            let mut file = File::open(profiles_file).unwrap();
-		        file.read_to_string(&mut data).unwrap();
-			}
-            let mut profiles_json: LauncherProfiles = serde_json::from_str(&data).unwrap();
+    for download in downloads {
+        if download.status != Download::Complete {
+            manager.enqueue(downloads::Job {
+                meta: profile.meta.clone(),
+                modinfo: download,
+                mods_dir: mods_dir.clone()
+            }, 0);
+        }
+    }
+    Ok(())
+}
 
-            let loader: &str = match current_profile.meta.loader {
-                ModLoader::Fabric => "fabric",
-                ModLoader::Forge => "forge",
-            };
+fn DownloadPage(cx: Scope) -> Element {
+    let ar = use_atom_root(&cx);
+    let state = (*ar.read(STATE)).clone();
 
-            let key = format!("modtool-rs-{}-{}-{}", loader, current_profile.meta.version, current_profile.meta.id);
+    let (manager, events) = cx.use_hook(|_| {
+        let (manager, events) = downloads::DownloadManager::new(settings::settings().parallel_downloads);
+        (manager, Some(events))
+    });
+    let manager = manager.clone();
+    let events = events.take();
+    // The list is only shown once the mods are looked up and the folder is synced
+    let ready = use_state(&cx, || false);
 
-			let mut version = String::from("");
-			for file in std::fs::read_dir(MC_DATA.profiles_dir.clone()).unwrap() {
-				let file = file.unwrap();
-				let file_str = file.file_name().into_string().unwrap();
-				if file_str.contains(loader) && file_str.contains(current_profile.meta.version.as_str()) {
-					version = file.file_name().into_string().unwrap()
-				}
-			}
+    use_future(&cx, (), |_| {
+        let ar = ar.clone();
+        let manager = manager.clone();
+        let ready = ready.clone();
+        async move {
+            match prepare_downloads(&ar, &manager).await {
+                Ok(_) => ready.set(true),
+                Err(err) => show_error(&ar, err, Page::DownloadPage, Some(Page::ProfilePage))
+            }
+        }
+    });
 
-            if !profiles_json.profiles.contains_key(&key) {
-
-                let date: DateTime<Utc> = Utc::now();
-                let date_str: String = date.to_rfc3339_opts(SecondsFormat::Millis, true); 
-
-                let profile = LauncherProfile {
-                    created: Some(date_str),
-                    lastUsed: String::from("1970-01-02T00:00:00.000Z"),
-                    lastVersionId: version,
-                    javaArgs: Some(String::from("-Dterminal.jline=false -Dterminal.ansi=true -XX:+UseG1GC -XX:+ParallelRefProcEnabled -XX:MaxGCPauseMillis=200 -XX:+UnlockExperimentalVMOptions -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:G1HeapWastePercent=5 -XX:G1MixedGCCountTarget=4 -XX:G1MixedGCLiveThresholdPercent=90 -XX:G1RSetUpdatingPauseTimePercent=5 -XX:SurvivorRatio=32 -XX:+PerfDisableSharedMem -XX:MaxTenuringThreshold=1 -XX:G1NewSizePercent=30 -XX:G1MaxNewSizePercent=40 -XX:G1HeapRegionSize=8M -XX:G1ReservePercent=20 -XX:InitiatingHeapOccupancyPercent=15 -Dusing.aikars.flags=https://mcflags.emc.gs -Daikars.new.flags=true")),
-                    name: format!("{} (ModTool RS)", current_profile.meta.name),
-                    r#type: String::from("custom"),
-                    icon: String::from("data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAJYAAACWCAYAAAA8AXHiAAAABHNCSVQICAgIfAhkiAAADxVJREFUeF7tnXtwVNUdx89Z8gACCRFQEkgCQ8RsfI2ItpMExD6odHDEx4za1vGNU/tPp9rRoX+IM9XqjLb9p04VbXWsaKtSHJ1RqS0qCa0Uqc9sSGOFDWxECIEEJJBkb7+/a27cxE129+459/k7M3eysPf+7jnf+9nf+d3fOedeKRSV+vr6omPJ8lXJiNEQMURlUsiKiBCVhhAVOMV0RadhM2oU6JNCdCWFSESE0ZWU+JuU20oiPa+0traeVHEK2Ldfqs9uKjcGjRVCGCukkD+Apcn2rfGRHlCg3xDGBiHkZlkgN8c/bO6xWydbYNVEl1YY0rhNGMatOHGl3ZPzcZ5WICGkXC8N+eie2NauXGuaE1gmUMbQGpxwDQOVq9S+3d8WYFmDVRVtul0K4xcMlG8BybfiCUPI+zpjzY9kYygrsKqjjeth7JZsDPI+gVfg8XishUKgCUtGsKqjDe8imFucyRB/HyYFjJ3x2LbzJ2rxhGDBUyFbwIUVSK8APNe4/Iz7BaDaBHOXsaiswAQKvAS4Vqf7Pi1Y1dGmu5CbeoAlZQUyKyDvjseaHxy739fAqq5rvF5I8WRmg7wHKzCsgCFuiLe1PJWqxyiwzDyVSO7ADpz0ZGpyUSAhRWRJaiJ1FFjV9U3rkE2/JxeLvC8rYCog5b3x1uZ1lhojYLG3YkDyVGCU1xoBq7qu4R5QN0Jcnifhw8OoQIrXMsGaW3fhzEmy8AOOrcJIg9I2J4aMgXP2tW3vNsGqqmu4QUr5R6WnYGOhVMAwjBs727Y9aYJVXd/4gjDElaFUghutVgEpXoy3tlwla2tXFp8s7O2F9SK1Z2BrIVXgZNFAaamsijZeDbf1XEhF4GZrUAADzNfIqvqGhzBL8A4N9tlkSBXA7OKHZU20YQMmcF0bUg242RoUwITQZ6kr3IKucLkG+2wypAqgK3wTHqtxFz4sCqkG3GwNCsBRtUvMu6I7Ql73l0Hg6ZOGxILJ/WJ+8QlRhs8d/ZNF/ESx2HeSb6bTSNdHYPEs0TTKFEpDLCvtFReVHRFnTf1CVAOodOXI0CSxG5C19E0Xbx8pM4HjgjFpBms0BhdMOyqWAaaLAFVlUe6Lgv8JwN4CYG/3lorPBwpDyxiDhUt/xpTjpncioKL4rKIcT0bEW70A7EipCVk//h2mElqwKuCNTJiwfWN6n9Zr/hk8F3WTBNi/4NHCUEIF1tRI0oyZLKCK8W+nS/vxKSZgtLV+MdXp0zt2vlCA1QiPRN0cATW7cMAxcTOdaMfRaSZgFJMF7e4ysGDRnZzlnRYiTeDlMmjIEcAItD7cafq9BAosSglYKYLzSo758tocGiww47G3ANhWbH4tvgdrRsHgSMxEHirjMwN8dKV2IwFrpS7eP1bio5r7NI9FN+5WzET5pjLAFfTyAcAiL0Zd5ac+SML6ymMtRvdmATVeJjzogFH7qIs07yzRZXaj6/Ri8TxYtQi8LZgoIOfylQIU5FuAkTejmwCvFE+CRSkB6uIIqAbNyUs7F4K6IhqQ9lKhdIUV9FMaw+3iGbAoWWne0Q0DRclML5XtyJi/eniGeLWn3PQMdRj6uaS8R6zENtNjMV6MkrDDd5aUkHWjuA4Wjc1desohE6o5NgZ9dYrWhovyGkAimCaKZWjgmgAj0IowK8JL5R38IP4OyDZ2z3S0Wq6BRUBdPrNbXIHNSyWBLuW1Yc9k5+7r2+i+CbLl+OulQl7sr4DLKcBcAevm0/aLH8/5zDO6UxC8eRim9xTli0owGXDFjMPiEmznw6N5pRBgd++er30IyXGwrpl1UNw5d5/rOlOH9QZgIu9ESUidhWZSfBeArcR2uqJpOfnUl3JiPwNchzWmKhwFaxViqXVVnflokvexNG3F8k4DLtye09wvEzJ0l6e5OCDegvTEnYBLlwaOgUUx1dOL2vMGw46BjzE9hbwT3dUd9NCsTgr6CTIK+t24C6Z46/698+xImvEYx8BaO2+vo4E6LXQgmF7H9okPhkAo6P8OICPQnCzXtS8SFHepLo6A5ZS36kHMQDD9DdtORUG4asEz2ZuGoN8EDKDpntlKddHltRwBS6e3ohiBYKJczZuag/BMUKj+nhZzfAnZYRGdqmYufro66vBajoC1sa5t3OVTdi9GM4LPNwASeacTIVioQJl+q6ucqziR/CvEWS8qTqBqB4uGO14/82O7/Iw6juYkEUzkoQ54KAhX0rgcjFyIoJ8go60UXWe+5QVA9YDiIF47WEsgwu8XfmK77f9D4E0gEVD0mctoBUzAhgN/u9pQXuumjlq7h6c9TjtYP5p9QPy0MqGk0uyxvpRRtceiNZBLPzxbyTWyjGgHaw2Gb9ZoGL7hGEspB2LJ++cqNehbsCwV+K5QDQ8M1gQ6ch7LPmQMVpbaceY9S6GGd2OwctPL3JvHCjOLxmBl1mjCPXh2Q3p5GKw8wbIOD/N8rHQSMliKwEo1E6YZpOPJx2BpACvVZFDnvGeSjcHKpJDC74OwSidbORisbJVSvJ+f1hXaaTqDZUc1xcd4cSX0Tqx+pmX25GUftTHoz2AphsTP5qwVz/T8hl3D04tpqRmD5eer6lLd92Me2maszCbvlG4NJIPl0oXx42npWRGbDp1iPo9hW4anLjNYfrzCDteZ1j/+gx7ugS3b9X8MlsMXyS+nexdB+Cvo6sg70etUci0MVq6KBXh/etbo8wdnmQ9R68rzpU8MVoBByaZpNIxEMNGzI1TO5WewslE/gPu8hCCcHif0kaa3UjBYAYRmvCbR/PznARM9iEN3YbB0K+yyfUpeUlf3MjyUk8/4Y7BcvvA6Tk+LaP8CmMg7HbVxR6eiTgyWChU9ZoNSBbd9stDVWjFYrsqv5+QM1le6+n5doR5E7FllsBgse+RkOIrBYrAYrDEK8HwsLUioMcoeiz2WGpLGWGGwGCwGi7tCLQxoMcoeiz0Wg8UeSwsDWoyyx2KPxWCxx9LCgBaj7LHYYzFY7LG0MKDFKHss9lgMFnssLQxoMcoeiz0Wg8UeSwsDWoyyx2KPxWCxx9LCgBaj7LHYYzFY7LG0MKDFKHss9lgMFnssLQxoMcoeiz0Wg8UeSwsDWoyyx3LQY30P7yy+r2aPlgvpNaNugTU5khRXzewWV806KObZeMP9XjzsbXUsqlRO7Suhayf3i+fO2KW00l415jRYl51yyIQpOuV4XpJswWMpf757fl42xh6sHaxJ0hBbzvpITMWvKujFCbC+hTfWE0z0wnFV5bHP5ojH9p+mypxpRztYdJInajvEuSXHlFbci8Z0gXUBICKYLi7tFRH8UFUX8lbktVQWR8C6Av3/2nl7Vdbbk7ZUglWH7u1K6LYcHqq8YFBbe+kBcde1L1Ju3xGwqNZPL2rPOxZQ3nrFBvMFay4C78sB08WAqab4hOLapTd3/955YiMeFKe6OAZWGLyWHbBKJw2J1eSZ0M2d43C4oMtbORZjWb+Gh+bvNl17UEsuYNEdHXmmJgDlVlm7p0bQ2y90FMc8FlW+BL/OXwMuepxhEEsmsAgk+mHRX7fvkn+JLnCThi7Quq6OgkUnPbVwQPxmwafijDxzL14EMx1Yi/Ejors5AqrCRvJSRzt/m6gUfzowW4fpEZuOg0VnXoCk6Y2nfi6+X96jtXFOG7fAoqSw5Zm89APai1erPENPdcamu7gCltWoZfgl/3D2gcB0jQTWEBqnMnmpAoCTeC3dBnioZ7D1DBaoMJnRhqtgWbUjz7UUkBFoxSHI0Ge8Kop2aEeOaivehkHJT3qlr5PFE2BZDZ6D+GspYhGCrGF6n5M6BOZc+zCgTDC9faRUbD863bV2eQqsVBUWIk4hwGgLw3BQPgQcRvdmwoSN/tIbWd0ungUrVZhzpn4x4skoMOYizDeujsBk86WZOnX0BVipAnwTXSTFYuTJvHL7rvMCjbX9Dt4JbXmmRJ4vzdRZb9+BZYlRhFF+iscsyGhoJKiF3m1owgTP9N/+yb5opm/BSlWXRv+tu0r6S3PA/F4+BUCWZ3rvWInvmhMIsFJVp6m5liejeUx+KvvxWjqKm2hz4qWZOrUJHFipYlHW2/Jk9bgB8GKh9xpaMJGH6k9GvFjNnOsUaLBS1ViMKSmWJ3NqrtN4V4M66hGYEDd1O5QNz5mOPA4IDVipGln5MQr8ZyEp61ShIR8rRRDHuF2QC4FFE4LcS9G6qC5NXVk2nOknyKZoGE6ioZQvM+FlgibWhaT0yZpo4y64ZvWTnn2m4Gx4Lit1ke/ku054I+uObge8VNgK8v7tsirauAUfloet8RO1dz7mm1ue7LwspwsfQpxkwUT5puAvdhtfQTiqN+GxGjYYQl7LYKVX4EzcTZ6OYSSaQ2ZugG4G8mYdyDN1oGvbA+9E8dJ/kGvqc+nN9V67dlIYz8qq+oaHpCHv8FrluD7+VcCQxsOypq7xCoxnvujfZnDNvaYABj6ulPX19UVHjXJaOuOPQSivqcj1GatA/zTZU2ZO3KmKNjwhhbyJNWIF8lXAEMYfOmPbbh4Gq/FqfHguX6N8PCuAO8JrOmMtfzbBqj67qRzTDj/Cx0qWhhXIQ4GEKJBnxT9s7hmZw1pd37ROGMY9eRjlQ8OugJT3xlub15EMI2DVRJdWGCK5g71W2Omw3f6EFJEle2Jbu0aBZXaJ7LVsqxr6A1O81dfAYq8VejzsCjDKW30NLPqPqmjT7UjJ/87uGfi48CmAIcGfdMaaH0ltedoFaJhKsx473RI+ibjFNhR4PB5ruXXsceOubKyONryL2H6xjRPxIaFRwNgZj207P11zJ1wyC8/l/+UuobnIzjcUnmpcfjKuxQZcm1Dly5yvNp/Rwwq8BKhWT1S/jGCZaYho011CGA94uKFcNccUkHfHY80PZjpdVmCZcNU1Xo906v34yMM+mVQN5vcJYYi18baWp7JpXtZgkTEzz2UMrRFSrmHAspE3EPskcL3XYzLoo1ZWPZtW5QSWZdAETBq3YWyRbjPZg2WjtP/2sQWU1UxbYFkHz627cGZEFFwqI3IV3OSl+P8i/+nHNU5R4CTCnZeNpPFKUgy+vK9te7dddfICK/WktbUri08U9q4W0rggYojKpJAVWCxeiXxFBfYL5bpFuxfFgeP6cOG7sJIoERFGV1IKxE/y38UDpZs6Ol5V8kqM/wNvVYi23YKH4QAAAABJRU5ErkJggg==")
-                };
-                    
-                profiles_json.profiles.insert(key, profile);
-
-				let profiles_file = MC_DATA.base_dir.clone() + "launcher_profiles.json";
-				let mut file = std::fs::OpenOptions::new().write(true).open(profiles_file).unwrap();
-
-                match file.write(&serde_json::to_string_pretty(&profiles_json).unwrap().into_bytes()) {
-					Ok(_) => {},
-					Err(err) => println!("{}", err)
-				};
+    use_future(&cx, (), |_| {
+        let ar = ar.clone();
+        async move {
+            let mut events = match events {
+                Some(events) => events,
+                None => return
+            };
+            while let Some(event) = events.recv().await {
+                handle_download_event(&ar, event);
             }
         }
     });
 
+    use_future(&cx, (), |_| { 
+        let state = state.clone();
+        let ar = ar.clone();
+        async move {
+            let res = match (state.manifest.lookup(state.selected_profile), state.java.as_ref()) {
+                (Some(current_profile), Some(java)) => install::write_launcher_profile(&current_profile, state.installed_version.as_str(), java),
+                (None, _) => Err(missing_profile(state.selected_profile)),
+                (_, None) => Err(error::ModtoolError::JavaNotFound)
+            };
+            match res {
+                Ok(_) => {},
+                Err(err) => show_error(&ar, err, Page::DownloadPage, Some(Page::ProfilePage))
+            };
+        }
+    });
+
     let state = use_read(&cx, STATE);
-    let current_profile = state.manifest.lookup(state.selected_profile).clone();
+    let current_profile = selected_profile(ar, state)?;
+    let mods_dir = current_profile.meta.mods_dir();
     let total_downloads = state.download_list.downloads.iter().len() as i32;
-    let finished_downloads = use_state(&cx, || 0 as i32);
-    let remaining_downloads = total_downloads - *finished_downloads.current();
+    let finished_downloads = state.download_list.downloads.iter()
+        .filter(|download| match download.status {
+            Download::Complete | Download::Failed | Download::Cancelled => true,
+            Download::Queued | Download::InProgress => false
+        })
+        .count() as i32;
+    let remaining_downloads = total_downloads - finished_downloads;
+    let all_finished = *ready.get() && finished_downloads == total_downloads;
+
+    // Checked again whenever the downloads finish, including after dependencies were added.
+    // None until the first check is done.
+    let missing = use_state(&cx, || None as Option<Vec<dependencies::MissingDependency>>);
+    let not_found = use_state(&cx, Vec::<String>::new);
+    let adding = use_state(&cx, || false);
+    use_future(&cx, (&all_finished,), |(all_finished,)| {
+        let missing = missing.clone();
+        let mods_dir = mods_dir.clone();
+        let ar = ar.clone();
+        async move {
+            if !all_finished {
+                return
+            }
+            match dependencies::missing_dependencies(mods_dir.as_str()) {
+                Ok(list) => missing.set(Some(list)),
+                Err(err) => show_error(&ar, err, Page::DownloadPage, Some(Page::ProfilePage))
+            }
+        }
+    });
+    let missing_list = match missing.get() {
+        Some(list) if all_finished => list.clone(),
+        _ => Vec::new()
+    };
+    let can_add = !*adding.get() && missing_list.iter().any(|dependency| !not_found.contains(&dependency.id));
+    let dependencies_ok = all_finished && missing.get().as_ref().map(|list| list.is_empty()).unwrap_or(false);
+    let add_manager = manager.clone();
+
+    let paused = use_state(&cx, || false);
     let atoms = use_atom_root(&cx);
     let mut sorted_state = state.download_list.downloads.clone();
-    sorted_state.sort_by(|a, b| {
-        if a.status == Download::InProgress && b.status == Download::InProgress { return Ordering::Equal };
-        if a.status == Download::Complete && b.status == Download::Complete { return Ordering::Equal };
-        if a.status == Download::InProgress && b.status == Download::Complete { return Ordering::Less };
-        if a.status == Download::Complete && b.status == Download::InProgress { return Ordering::Greater };
-        Ordering::Equal
+    sorted_state.sort_by_key(|download| match download.status {
+        Download::InProgress => 0,
+        Download::Queued => 1,
+        Download::Failed => 2,
+        Download::Cancelled => 3,
+        Download::Complete => 4
     });
+    let pause_manager = manager.clone();
+    let pause_label = match *paused.get() {
+        true => "RESUME",
+        false => "PAUSE"
+    };
 
     cx.render(rsx! {
         div {
@@ -1149,11 +1547,74 @@ fn DownloadPage(cx: Scope) -> Element {
                         class: "text-6xl font-bold text-slate-100 text-right",
                         "{current_profile.meta.name}"
                     }
+                    (!*ready.get()).then(|| rsx! {
+                        p {
+                            class: "text-slate-300 text-xl text-right",
+                            "Looking up mods..."
+                        }
+                    }),
+                    dependencies_ok.then(|| rsx! {
+                        p {
+                            class: "text-emerald-400 text-xl text-right",
+                            "Every mod has the dependencies it needs."
+                        }
+                    }),
+                    (!missing_list.is_empty()).then(|| rsx! {
+                        div {
+                            class: "bg-slate-900 rounded-xl p-6 flex flex-col gap-2 text-right",
+                            h3 {
+                                class: "text-orange-400 text-2xl font-bold",
+                                "MISSING DEPENDENCIES"
+                            },
+                            missing_list.iter().map(|dependency| {
+                                let required_by = dependency.required_by.join(", ");
+                                let status = match not_found.contains(&dependency.id) {
+                                    true => " (not on Modrinth, install it by hand)",
+                                    false => ""
+                                };
+                                rsx! {
+                                    p {
+                                        key: "{dependency.id}",
+                                        class: "text-slate-300",
+                                        span { class: "text-slate-100 font-bold", "{dependency.id}" },
+                                        " needed by {required_by}{status}"
+                                    }
+                                }
+                            }),
+                            can_add.then(|| rsx! {
+                                button {
+                                    class: "bg-green-500 hover:bg-green-700 rounded-xl p-4 font-bold text-slate-100 self-end",
+                                    onclick: move |_| {
+                                        let ar = ar.clone();
+                                        let manager = add_manager.clone();
+                                        let missing_list = missing_list.clone();
+                                        let not_found = not_found.clone();
+                                        let adding = adding.clone();
+                                        adding.set(true);
+                                        cx.spawn(async move {
+                                            match add_dependencies(&ar, &manager, missing_list).await {
+                                                Ok(ids) => {
+                                                    let mut list = (*not_found.current()).clone();
+                                                    list.extend(ids);
+                                                    not_found.set(list);
+                                                },
+                                                Err(err) => show_error(&ar, err, Page::DownloadPage, Some(Page::ProfilePage))
+                                            }
+                                            adding.set(false);
+                                        });
+                                    },
+                                    "ADD FROM MODRINTH"
+                                }
+                            })
+                        }
+                    }),
                     div {
                         class: "jusify-end self-end justify-self-end flex flex-col gap-6",
                         sorted_state.iter().map(|modinfo| {
+                            let mods_dir = mods_dir.clone();
+                            let manager = manager.clone();
                             rsx! {
-                                DownloadItem { modinfo: modinfo.clone() , downloads_complete: finished_downloads.clone()}
+                                DownloadItem { key: "{modinfo.url}", modinfo: modinfo.clone(), mods_dir: mods_dir, manager: manager }
                             }
                         })
                     }
@@ -1190,8 +1651,21 @@ fn DownloadPage(cx: Scope) -> Element {
                     div {
                         class: "grow flex-1"
                     },
+                    (remaining_downloads > 0).then(|| rsx! {
+                        button {
+                            class: "bg-slate-800 hover:bg-slate-700 rounded-xl p-4 font-bold text-slate-100",
+                            onclick: move |_| {
+                                match *paused.get() {
+                                    true => pause_manager.resume(),
+                                    false => pause_manager.pause()
+                                };
+                                paused.set(pause_manager.is_paused());
+                            },
+                            "{pause_label}"
+                        }
+                    })
                 }
-                match *finished_downloads.get() == total_downloads {
+                match all_finished {
                     true => rsx! { 
                         button {
                             class: "bg-green-500 hover:bg-green-700 rounded-xl justify-self-end p-6 mt-auto self-center",
@@ -1225,71 +1699,85 @@ fn DownloadPage(cx: Scope) -> Element {
     })
 }
 
-#[inline_props]
-fn DownloadItem(cx: Scope, modinfo: ModDownload, downloads_complete: UseState<i32>) -> Element {
-
-    let download_state = use_state(&cx, || Download::InProgress);
-    let downloaded_bytes = use_state(&cx, || 0 as u64);
-    let total_bytes = use_state(&cx, || 1 as u64);
-    let percentage = use_state(&cx, || 0 as u64);
-
-    let ar = use_atom_root(&cx);
-
-    use_future(&cx, (),  |_| {
-        let downloads_complete = downloads_complete.clone();
-        let download_state = download_state.clone();
-        let mods_dir = MC_DATA.mods_dir.clone();
-        let modinfo = modinfo.clone();
-        let mut downloaded_bytes = downloaded_bytes.clone();
-        let total_bytes = total_bytes.clone();
-        let percentage = percentage.clone();
-
-        let ar = ar.clone();
-
-        async move {
-            let sep = match cfg!(windows) {
-                true => "\\",
-                false => "/",
-            };
-
-            let path = Path::new(&modinfo.url);
-			let filename = path.file_name().unwrap();
-			let filepath = mods_dir.clone() + sep + filename.to_str().unwrap();
-            let mut fhandle = File::create(filepath.clone()).unwrap();
-
-            while *ar.read(NUM_DOWNLOADS) >= 4 {
-                async_std::task::sleep(std::time::Duration::from_millis(100)).await;
+// Queues every missing dependency that can be found on Modrinth, and adds it to the profile so the
+// rest of the install knows about it. Returns the ids that couldn't be found.
+async fn add_dependencies(ar: &AtomRoot, manager: &downloads::DownloadManager, missing: Vec<dependencies::MissingDependency>) -> error::Result<Vec<String>> {
+    let state = (*ar.read(STATE)).clone();
+    let meta = state.manifest.lookup(state.selected_profile).ok_or_else(|| missing_profile(state.selected_profile))?.meta;
+    let mods_dir = meta.mods_dir();
+    let mut not_found = Vec::new();
+    for dependency in missing {
+        let modinfo = match dependencies::find_dependency(&dependency, &meta).await? {
+            Some(modinfo) => modinfo,
+            None => {
+                not_found.push(dependency.id);
+                continue
             }
+        };
+        let mut download = install::mod_download(&modinfo);
+        download.dependency = true;
 
-            ar.set(NUM_DOWNLOADS.unique_id(), *ar.read(NUM_DOWNLOADS) + 1);
-
-            let res = HTTP_CLIENT
-                .get(modinfo.url.clone())
-                .header("User-Agent", format!("Starkiller645/modtool-rs/{APP_VERSION} (tallie@tallie.dev)"))
-                .send()
-                .await.unwrap();
-
-            let content_length = res.content_length().unwrap().clone();
-            total_bytes.set(content_length);
-
-            let mut bytes_stream = res.bytes_stream();
-            while let Some(item) = bytes_stream.next().await {
-                let chunk = item.or(Err(format!("Error downloading file!"))).unwrap();
-                fhandle.write_all(&chunk)
-                    .unwrap();
-                downloaded_bytes += chunk.len() as u64;
-                percentage.set(((*downloaded_bytes.current() as f64 / *total_bytes.current() as f64) * 100.0) as u64);
+        let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+        for profile in state_cpy.manifest.profiles.iter_mut() {
+            if profile.meta.id == meta.id {
+                profile.mods.push(modinfo.clone());
             }
+        }
+        state_cpy.download_list.downloads.push(download.clone());
+        ar.set(STATE.unique_id(), state_cpy);
+
+        manager.enqueue(downloads::Job {
+            meta: meta.clone(),
+            modinfo: download,
+            mods_dir: mods_dir.clone()
+        }, 0);
+    }
+    Ok(not_found)
+}
 
-            ar.set(NUM_DOWNLOADS.unique_id(), *ar.read(NUM_DOWNLOADS) - 1);
+fn update_download(ar: &AtomRoot, url: &str, update: impl FnOnce(&mut ModDownload)) {
+    let mut state_cpy: AppState = (*ar.read(STATE)).clone();
+    if let Some(download) = state_cpy.download_list.downloads.iter_mut().find(|download| download.url == url) {
+        update(download);
+    }
+    ar.set(STATE.unique_id(), state_cpy);
+}
 
-            download_state.set(Download::Complete);
-            download_state.needs_update();
-            downloads_complete.set(*downloads_complete.current() + 1);
-            downloads_complete.needs_update();
+fn handle_download_event(ar: &AtomRoot, event: downloads::DownloadEvent) {
+    match event {
+        downloads::DownloadEvent::Queued { url } => update_download(ar, url.as_str(), |download| download.status = Download::Queued),
+        downloads::DownloadEvent::Started { url } => update_download(ar, url.as_str(), |download| download.status = Download::InProgress),
+        downloads::DownloadEvent::Progress { url, bytes_read, bytes_total } => update_download(ar, url.as_str(), |download| {
+            download.bytes_read = bytes_read as i32;
+            download.bytes_total = bytes_total as i32;
+        }),
+        downloads::DownloadEvent::Cancelled { url } => update_download(ar, url.as_str(), |download| download.status = Download::Cancelled),
+        downloads::DownloadEvent::Finished { url, result } => {
+            // Download problems can be retried from the item, anything else needs the error page
+            let status = match result {
+                Ok(_) => Download::Complete,
+                Err(error::ModtoolError::HashMismatch { .. })
+                    | Err(error::ModtoolError::ManualDownload { .. })
+                    | Err(error::ModtoolError::Network(_))
+                    | Err(error::ModtoolError::Timeout { .. }) => Download::Failed,
+                Err(err) => {
+                    show_error(ar, err, Page::DownloadPage, Some(Page::ProfilePage));
+                    return
+                }
+            };
+            update_download(ar, url.as_str(), |download| {
+                if status == Download::Complete {
+                    download.bytes_read = download.bytes_total;
+                }
+                download.status = status;
+            });
         }
-    });
+    }
+}
 
+#[inline_props]
+fn DownloadItem(cx: Scope, modinfo: ModDownload, mods_dir: String, manager: downloads::DownloadManager) -> Element {
+    let ar = use_atom_root(&cx);
 
     let provider: String = match modinfo.provider {
         ModProvider::Modrinth => String::from("Modrinth"),
@@ -1305,15 +1793,37 @@ fn DownloadItem(cx: Scope, modinfo: ModDownload, downloads_complete: UseState<i3
         ModProvider::Unknown => String::from("text-slate-300")
     };
 
-    let percentage_decimal = *downloaded_bytes.current() as f64 / *total_bytes.current() as f64;
+    let total_bytes = modinfo.bytes_total.max(1) as u64;
+    // Mods left over from the last install never get progress events
+    let downloaded_bytes = match modinfo.status {
+        Download::Complete => total_bytes,
+        _ => (modinfo.bytes_read.max(0) as u64).min(total_bytes)
+    };
+    let percentage_decimal = downloaded_bytes as f64 / total_bytes as f64;
     let percent = percentage_decimal * 100.0;
+    let percentage = percent as u64;
     let red = 252 - (percentage_decimal * 200.0) as u64;
     let green = 165 + (percentage_decimal * 46.0) as u64;
     let blue = 165 - (percentage_decimal * 12.0) as u64;
 
     let hex = format!("#{:X}{:X}{:X}", red, green, blue);
 
-    let mb = format!("{:.2}MB", *total_bytes.current() as f64 / 1000000.0);
+    let mb = format!("{:.2}MB", total_bytes as f64 / 1000000.0);
+
+    let url = modinfo.url.clone();
+    let cancel_manager = manager.clone();
+    let retry_manager = manager.clone();
+    let job_modinfo = ModDownload {
+        bytes_read: 0,
+        status: Download::Queued,
+        ..modinfo.clone()
+    };
+    let job_mods_dir = mods_dir.clone();
+    let manual_page = match modinfo.manual && modinfo.status == Download::Failed {
+        true => Some(modinfo.url.clone()),
+        false => None
+    };
+    let downloads_dir = install::downloads_dir().unwrap_or_else(|_| String::from("your Downloads folder"));
 
     cx.render(rsx! {
         div {
@@ -1347,14 +1857,34 @@ fn DownloadItem(cx: Scope, modinfo: ModDownload, downloads_complete: UseState<i3
                     class: "text-slate-100 text-right font-bold py-2",
                     "{percentage}%"
                 }
+                manual_page.map(|page| rsx! {
+                    p {
+                        class: "text-sm text-slate-500 italic text-right",
+                        "This mod has to be downloaded by hand from ",
+                        a {
+                            class: "text-sm text-sky-500 italic underline",
+                            href: "{page}",
+                            "{page}"
+                        },
+                        ". Save it in {downloads_dir} and retry."
+                    }
+                })
             }
-            match *download_state.current() {
-                Download::InProgress => rsx! {
-                    img {
-                        src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
-                        height: "32",
-                        width: "32",
-                        class: "fill-slate-100 shrink ml-auto align-center object-scale-down"
+            match modinfo.status {
+                Download::Queued | Download::InProgress => rsx! {
+                    div {
+                        class: "flex flex-col gap-2 shrink ml-auto align-center",
+                        img {
+                            src: "https://tallie.dev/modtool/assets/loader-slate-900.gif",
+                            height: "32",
+                            width: "32",
+                            class: "fill-slate-100 object-scale-down"
+                        },
+                        button {
+                            class: "bg-slate-800 hover:bg-slate-700 rounded-xl p-2 text-slate-100 text-sm font-bold",
+                            onclick: move |_| cancel_manager.cancel(url.as_str()),
+                            "Cancel"
+                        }
                     }
                 },
                 Download::Complete => rsx! {
@@ -1364,6 +1894,34 @@ fn DownloadItem(cx: Scope, modinfo: ModDownload, downloads_complete: UseState<i3
                         width: "32",
                         class: "fill-orange-500 shrink ml-auto align-center"
                     }
+                },
+                Download::Failed | Download::Cancelled => rsx! {
+                    div {
+                        class: "flex flex-col gap-2 shrink ml-auto align-center",
+                        img {
+                            src: "https://tallie.dev/modtool/assets/fa-xmark-circle.svg",
+                            height: "32",
+                            width: "32",
+                            class: "fill-red-500"
+                        },
+                        button {
+                            class: "bg-slate-800 hover:bg-slate-700 rounded-xl p-2 text-slate-100 text-sm font-bold",
+                            onclick: move |_| {
+                                let state = ar.read(STATE);
+                                let meta = match state.manifest.lookup(state.selected_profile) {
+                                    Some(profile) => profile.meta,
+                                    None => return
+                                };
+                                // Retries jump the queue
+                                retry_manager.enqueue(downloads::Job {
+                                    meta,
+                                    modinfo: job_modinfo.clone(),
+                                    mods_dir: job_mods_dir.clone()
+                                }, 1);
+                            },
+                            "Retry"
+                        }
+                    }
                 }
             }
         }
@@ -1390,25 +1948,399 @@ fn ProfilePage(cx: Scope) -> Element {
                         }
                     }})
             },
-            button {
-                onclick: move |_| {
-                    match state.manifest.lookup(state.selected_profile).meta.loader { 
-                    ModLoader::Forge =>  {
+            div {
+                class: "flex flex-col my-auto",
+                button {
+                    onclick: move |_| {
                         let mut state_cpy = state.clone();
-                        state_cpy.page = Page::ForgeCheckPage;
+                        // Get a Java new enough for this version before installing anything
+                        state_cpy.page = Page::JavaDownloadPage;
+                        state_cpy.java = None;
                         atoms.set(STATE.unique_id(), state_cpy);
                     },
-                    ModLoader::Fabric => {
+                    class: "hover:bg-green-700 bg-green-500 rounded-xl p-6 m-6 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-arrow-right.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                },
+                button {
+                    onclick: move |_| {
                         let mut state_cpy = state.clone();
-                        state_cpy.page = Page::FabricCheckPage;
+                        state_cpy.page = Page::BackupsPage;
                         atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-clock-rotate-left.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
                     }
-                }
                 },
-                class: "hover:bg-green-700 bg-green-500 rounded-xl p-6 m-6 grow-0 my-auto flex-0 shrink",
-                img {
-                  src: "https://tallie.dev/modtool/assets/fa-arrow-right.svg",
-                  height: "32",
+                button {
+                    onclick: move |_| {
+                        let mut state_cpy = state.clone();
+                        state_cpy.page = Page::ImportPage;
+                        atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-file-import.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                },
+                button {
+                    onclick: move |_| {
+                        let mut state_cpy = state.clone();
+                        state_cpy.page = Page::LauncherProfilesPage;
+                        atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-rocket.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                },
+                button {
+                    onclick: move |_| {
+                        let mut state_cpy = state.clone();
+                        state_cpy.page = Page::ProfileEditor;
+                        state_cpy.editing = None;
+                        atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-plus.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                },
+                (manifest::is_local(state.selected_profile)).then(|| rsx! {
+                    button {
+                        onclick: move |_| {
+                            let mut state_cpy = state.clone();
+                            state_cpy.page = Page::ProfileEditor;
+                            state_cpy.editing = Some(state.selected_profile);
+                            atoms.set(STATE.unique_id(), state_cpy);
+                        },
+                        class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                        img {
+                          src: "https://tallie.dev/modtool/assets/fa-pen.svg",
+                          height: "32",
+                          width: "32",
+                          class: "mx-auto fill-slate-100"
+                        }
+                    }
+                })
+            }
+        }
+    })
+}
+
+// Lists the mods folder backups in CACHE_DIR and restores one on request
+fn BackupsPage(cx: Scope) -> Element {
+    let state = use_read(&cx, STATE);
+    let atoms = use_atom_root(&cx);
+    let message = use_state(&cx, || String::from(""));
+
+    let backups = match mods_dir::list_backups() {
+        Ok(backups) => backups,
+        Err(err) => {
+            if message.get().is_empty() {
+                message.set(err.to_string());
+            }
+            Vec::new()
+        }
+    };
+
+    cx.render(rsx! {
+        div {
+            id: "backups",
+            class: "flex flex-row flex-1 h-full",
+            div {
+                class: "flex-1 rounded-xl text-slate-100 m-6 flex flex-col p-6 gap-6 overflow-y-auto",
+                h2 {
+                    class: "text-6xl font-bold",
+                    "Backups"
+                },
+                p {
+                    class: "text-xl text-slate-300",
+                    "Restoring a backup removes the mods ModTool installed and puts back the ones that were in your mods folder."
+                },
+                p {
+                    class: "text-xl text-orange-600 font-bold",
+                    "{message}"
+                },
+                (backups.is_empty()).then(|| rsx! {
+                    p {
+                        class: "text-xl text-slate-500 italic",
+                        "No backups yet."
+                    }
+                }),
+                backups.iter().map(|path| {
+                    let path = path.clone();
+                    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                    let message = message.clone();
+                    let restored_name = name.clone();
+                    rsx! {
+                        div {
+                            key: "{name}",
+                            class: "flex flex-row bg-slate-900 rounded-xl p-6 gap-6",
+                            p {
+                                class: "flex-1 text-2xl font-bold my-auto",
+                                "{name}"
+                            },
+                            button {
+                                class: "bg-green-500 hover:bg-green-700 rounded-xl p-6",
+                                onclick: move |_| {
+                                    match mods_dir::restore_backup(&path) {
+                                        Ok((restored, target)) => message.set(format!("Restored {} files from {} to {}", restored, restored_name, target)),
+                                        Err(err) => message.set(err.to_string())
+                                    }
+                                },
+                                img {
+                                    src: "https://tallie.dev/modtool/assets/fa-rotate-right.svg",
+                                    height: "32",
+                                    width: "32",
+                                    class: "mx-auto fill-slate-100"
+                                }
+                            }
+                        }
+                    }
+                })
+            },
+            button {
+                onclick: move |_| {
+                    let mut state_cpy = state.clone();
+                    state_cpy.page = Page::ProfilePage;
+                    atoms.set(STATE.unique_id(), state_cpy);
+                },
+                class: "hover:bg-red-700 bg-red-500 rounded-xl p-6 m-6 grow-0 my-auto flex-0 shrink",
+                img {
+                  src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
+                  height: "32",
+                  width: "32",
+                  class: "mx-auto fill-slate-100"
+                }
+            }
+        }
+    })
+}
+
+// Adds a modpack file to the local manifest and selects it
+fn ImportPage(cx: Scope) -> Element {
+    let state = use_read(&cx, STATE);
+    let atoms = use_atom_root(&cx);
+    let path = use_state(&cx, || String::from(""));
+    let message = use_state(&cx, || String::from(""));
+    let skipped = use_state(&cx, Vec::<String>::new);
+    let unresolved = use_state(&cx, Vec::<String>::new);
+
+    cx.render(rsx! {
+        div {
+            id: "import",
+            class: "flex flex-row flex-1 h-full",
+            div {
+                class: "flex-1 rounded-xl text-slate-100 m-6 flex flex-col p-6 gap-6 overflow-y-auto",
+                h2 {
+                    class: "text-6xl font-bold",
+                    "Import modpack"
+                },
+                p {
+                    class: "text-xl text-slate-300",
+                    "Enter the path of a Modrinth .mrpack or a CurseForge modpack zip. It's added to your profiles on this computer."
+                },
+                div {
+                    class: "flex flex-row gap-6",
+                    input {
+                        class: "flex-1 bg-slate-900 rounded-xl p-6 text-2xl",
+                        value: "{path}",
+                        placeholder: "Path to modpack",
+                        oninput: move |evt| path.set(evt.value.clone())
+                    },
+                    button {
+                        class: "bg-green-500 hover:bg-green-700 rounded-xl p-6 text-2xl font-bold",
+                        onclick: move |_| {
+                            match packs::import_pack(Path::new(path.get().trim())) {
+                                Ok(report) => {
+                                    let mut state_cpy = state.clone();
+                                    let id = report.profile.meta.id;
+                                    state_cpy.manifest.profiles.retain(|profile| profile.meta.id != id);
+                                    message.set(format!("Imported {} with {} mods", report.profile.meta.name, report.profile.mods.len()));
+                                    state_cpy.manifest.profiles.push(report.profile);
+                                    state_cpy.selected_profile = id;
+                                    atoms.set(STATE.unique_id(), state_cpy);
+                                    skipped.set(report.skipped);
+                                    unresolved.set(report.unresolved);
+                                },
+                                Err(err) => {
+                                    message.set(err.to_string());
+                                    skipped.set(Vec::new());
+                                    unresolved.set(Vec::new());
+                                }
+                            }
+                        },
+                        "Import"
+                    }
+                },
+                p {
+                    class: "text-xl text-orange-600 font-bold",
+                    "{message}"
+                },
+                (!skipped.is_empty()).then(|| rsx! {
+                    p {
+                        class: "text-xl text-slate-300",
+                        "Not installed from this pack:"
+                    }
+                }),
+                skipped.iter().map(|entry| rsx! {
+                    p {
+                        key: "{entry}",
+                        class: "text-lg text-slate-500",
+                        "{entry}"
+                    }
+                }),
+                (!unresolved.is_empty()).then(|| rsx! {
+                    p {
+                        class: "text-xl text-slate-300",
+                        "These only have a CurseForge id. They're looked up when you install, which needs curseforge_api_key in settings.json:"
+                    }
+                }),
+                unresolved.iter().map(|entry| rsx! {
+                    p {
+                        key: "{entry}",
+                        class: "text-lg text-slate-500",
+                        "{entry}"
+                    }
+                })
+            },
+            button {
+                onclick: move |_| {
+                    let mut state_cpy = state.clone();
+                    state_cpy.page = Page::ProfilePage;
+                    atoms.set(STATE.unique_id(), state_cpy);
+                },
+                class: "hover:bg-red-700 bg-red-500 rounded-xl p-6 m-6 grow-0 my-auto flex-0 shrink",
+                img {
+                  src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
+                  height: "32",
+                  width: "32",
+                  class: "mx-auto fill-slate-100"
+                }
+            }
+        }
+    })
+}
+
+// Saves the selected profile as a Modrinth pack, with whichever game directory folders are picked
+fn ExportPage(cx: Scope) -> Element {
+    let state = use_read(&cx, STATE);
+    let atoms = use_atom_root(&cx);
+    let profile = selected_profile(atoms, state)?;
+    let path = use_state(&cx, || {
+        // Without a Downloads folder the pack is saved next to ModTool
+        Path::new(install::downloads_dir().unwrap_or_default().as_str()).join(format!("{}.mrpack", profile.meta.name)).display().to_string()
+    });
+    let selected = use_state(&cx, || vec![String::from("config")]);
+    let message = use_state(&cx, || String::from(""));
+
+    // Everything in the game directory except the mods, which are listed in the index instead
+    let mut folders: Vec<String> = match std::fs::read_dir(profile.meta.game_dir()) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir() && entry.file_name() != "mods")
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(_) => Vec::new()
+    };
+    folders.sort();
+
+    cx.render(rsx! {
+        div {
+            id: "export",
+            class: "flex flex-row flex-1 h-full",
+            div {
+                class: "flex-1 rounded-xl text-slate-100 m-6 flex flex-col p-6 gap-6 overflow-y-auto",
+                h2 {
+                    class: "text-6xl font-bold",
+                    "Export {profile.meta.name}"
+                },
+                p {
+                    class: "text-xl text-slate-300",
+                    "Saves the installed mods as a Modrinth .mrpack that other launchers can import. Pick the folders to include with it:"
+                },
+                div {
+                    class: "flex flex-row flex-wrap gap-3",
+                    folders.iter().map(|folder| {
+                        let folder = folder.clone();
+                        let label = folder.clone();
+                        let is_selected = selected.contains(&folder);
+                        let selected = selected.clone();
+                        let class = match is_selected {
+                            true => "bg-cyan-700 hover:bg-cyan-800 rounded-xl p-3 text-xl",
+                            false => "bg-slate-900 hover:bg-slate-700 rounded-xl p-3 text-xl text-slate-400"
+                        };
+                        rsx! {
+                            button {
+                                key: "{label}",
+                                class: "{class}",
+                                onclick: move |_| {
+                                    let mut folders = selected.get().clone();
+                                    match is_selected {
+                                        true => folders.retain(|selected| selected != &folder),
+                                        false => folders.push(folder.clone())
+                                    }
+                                    selected.set(folders);
+                                },
+                                "{label}"
+                            }
+                        }
+                    })
+                },
+                div {
+                    class: "flex flex-row gap-6",
+                    input {
+                        class: "flex-1 bg-slate-900 rounded-xl p-6 text-2xl",
+                        value: "{path}",
+                        oninput: move |evt| path.set(evt.value.clone())
+                    },
+                    button {
+                        class: "bg-green-500 hover:bg-green-700 rounded-xl p-6 text-2xl font-bold",
+                        onclick: move |_| {
+                            let config_dirs: Vec<String> = selected.iter().filter(|folder| folders.contains(folder)).cloned().collect();
+                            match packs::export_mrpack(&profile, &config_dirs, Path::new(path.get().trim())) {
+                                Ok(report) => message.set(match report.bundled.len() {
+                                    0 => format!("Exported {} mods and {} other files", report.listed, report.overrides),
+                                    bundled => format!("Exported {} mods and {} other files. {} mods have no download url and were put in the pack", report.listed, report.overrides, bundled)
+                                }),
+                                Err(err) => message.set(err.to_string())
+                            }
+                        },
+                        "Export"
+                    }
+                },
+                p {
+                    class: "text-xl text-orange-600 font-bold",
+                    "{message}"
+                }
+            },
+            button {
+                onclick: move |_| {
+                    let mut state_cpy = state.clone();
+                    state_cpy.page = Page::Complete;
+                    atoms.set(STATE.unique_id(), state_cpy);
+                },
+                class: "hover:bg-red-700 bg-red-500 rounded-xl p-6 m-6 grow-0 my-auto flex-0 shrink",
+                img {
+                  src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
+                  height: "32",
                   width: "32",
                   class: "mx-auto fill-slate-100"
                 }
@@ -1417,13 +2349,530 @@ fn ProfilePage(cx: Scope) -> Element {
     })
 }
 
+// Turns what was typed into the editor's mod box into a manifest entry: a download url,
+// a CurseForge `projectID/fileID`, or otherwise a Modrinth slug
+fn parse_mod_entry(entry: &str) -> Option<Mod> {
+    let entry = entry.trim();
+    if entry.is_empty() {
+        return None
+    }
+    let mut modinfo = Mod {
+        name: String::from(entry),
+        url: String::new(),
+        version: String::new(),
+        provider: ModProvider::Modrinth,
+        size: 0,
+        project: None,
+        projectID: None,
+        fileID: None,
+        filename: None,
+        manual: false,
+        sha1: None,
+        sha256: None,
+        sha512: None
+    };
+    if entry.starts_with("http://") || entry.starts_with("https://") {
+        let filename = install::mod_filename(&None, entry)?;
+        modinfo.name = filename.trim_end_matches(".jar").to_string();
+        modinfo.url = String::from(entry);
+        modinfo.provider = packs::url_provider(entry);
+        return Some(modinfo)
+    }
+    if let Some((project, file)) = entry.split_once('/') {
+        let (project_id, file_id) = (project.parse::<u64>().ok()?, file.parse::<u64>().ok()?);
+        modinfo.name = format!("CurseForge project {}", project_id);
+        modinfo.provider = ModProvider::CurseForge;
+        modinfo.projectID = Some(project_id);
+        modinfo.fileID = Some(file_id);
+        return Some(modinfo)
+    }
+    modinfo.project = Some(String::from(entry));
+    Some(modinfo)
+}
+
+// Creates and edits the profiles kept in the local manifest
+fn ProfileEditor(cx: Scope) -> Element {
+    let state = use_read(&cx, STATE);
+    let atoms = use_atom_root(&cx);
+    let original = match state.editing {
+        Some(id) => match state.manifest.lookup(id) {
+            Some(profile) => Some(profile),
+            None => {
+                show_error(atoms, missing_profile(id), Page::HomePage, Some(Page::HomePage));
+                return None;
+            }
+        },
+        None => None
+    };
+    let draft = use_state(&cx, || match original.clone() {
+        Some(profile) => profile,
+        None => Profile {
+            meta: ProfileMeta {
+                name: String::new(),
+                loader: ModLoader::Fabric,
+                version: String::new(),
+                id: 0,
+                loader_version: None,
+                overrides: None
+            },
+            mods: Vec::new()
+        }
+    });
+    let entry = use_state(&cx, || String::from(""));
+    let message = use_state(&cx, || String::from(""));
+
+    let title = match original.as_ref() {
+        Some(profile) => format!("Edit {}", profile.meta.name),
+        None => String::from("New profile")
+    };
+    let loader_version = draft.meta.loader_version.clone().unwrap_or_default();
+    let mod_count = draft.mods.len();
+    let loaders = [
+        (ModLoader::Fabric, "Fabric", "bg-sky-700"),
+        (ModLoader::Forge, "Forge", "bg-orange-700"),
+        (ModLoader::Quilt, "Quilt", "bg-purple-700"),
+        (ModLoader::NeoForge, "NeoForge", "bg-amber-700")
+    ];
+
+    cx.render(rsx! {
+        div {
+            id: "editor",
+            class: "flex flex-row flex-1 h-full",
+            div {
+                class: "flex-1 rounded-xl text-slate-100 m-6 flex flex-col p-6 gap-6 overflow-y-auto",
+                h2 {
+                    class: "text-6xl font-bold",
+                    "{title}"
+                },
+                div {
+                    class: "flex flex-row gap-6",
+                    input {
+                        class: "flex-1 bg-slate-900 rounded-xl p-6 text-2xl",
+                        value: "{draft.meta.name}",
+                        placeholder: "Profile name",
+                        oninput: move |evt| draft.with_mut(|profile| profile.meta.name = evt.value.clone())
+                    },
+                    input {
+                        class: "w-48 bg-slate-900 rounded-xl p-6 text-2xl",
+                        value: "{draft.meta.version}",
+                        placeholder: "1.19.2",
+                        oninput: move |evt| draft.with_mut(|profile| profile.meta.version = evt.value.trim().to_string())
+                    },
+                    input {
+                        class: "w-48 bg-slate-900 rounded-xl p-6 text-2xl",
+                        value: "{loader_version}",
+                        placeholder: "Any loader version",
+                        oninput: move |evt| draft.with_mut(|profile| profile.meta.loader_version = match evt.value.trim() {
+                            "" => None,
+                            version => Some(String::from(version))
+                        })
+                    },
+                    loaders.iter().map(|(loader, name, colour)| {
+                        let loader = *loader;
+                        let class = match draft.meta.loader == loader {
+                            true => format!("{} rounded-xl p-6 text-2xl font-bold", colour),
+                            false => String::from("bg-slate-900 hover:bg-slate-700 rounded-xl p-6 text-2xl text-slate-400")
+                        };
+                        let draft = draft.clone();
+                        rsx! {
+                            button {
+                                key: "{name}",
+                                class: "{class}",
+                                onclick: move |_| draft.with_mut(|profile| profile.meta.loader = loader),
+                                "{name}"
+                            }
+                        }
+                    })
+                },
+                div {
+                    class: "flex flex-row gap-6",
+                    input {
+                        class: "flex-1 bg-slate-900 rounded-xl p-6 text-2xl",
+                        value: "{entry}",
+                        placeholder: "Mod url, Modrinth slug or CurseForge projectID/fileID",
+                        oninput: move |evt| entry.set(evt.value.clone())
+                    },
+                    button {
+                        class: "bg-cyan-700 hover:bg-cyan-800 rounded-xl p-6 text-2xl font-bold",
+                        onclick: move |_| {
+                            match parse_mod_entry(entry.get()) {
+                                Some(modinfo) => {
+                                    draft.with_mut(|profile| profile.mods.push(modinfo));
+                                    entry.set(String::new());
+                                    message.set(String::new());
+                                },
+                                None => message.set(format!("'{}' isn't a url, a Modrinth slug or a CurseForge projectID/fileID", entry.get().trim()))
+                            }
+                        },
+                        "Add"
+                    }
+                },
+                p {
+                    class: "text-xl text-orange-600 font-bold",
+                    "{message}"
+                },
+                (mod_count == 0).then(|| rsx! {
+                    p {
+                        class: "text-xl text-slate-500 italic",
+                        "No mods yet."
+                    }
+                }),
+                draft.mods.iter().enumerate().map(|(i, modinfo)| {
+                    let source = match (modinfo.project.as_ref(), modinfo.projectID, modinfo.fileID) {
+                        (Some(slug), _, _) => format!("Modrinth: {}", slug),
+                        (None, Some(project_id), Some(file_id)) => format!("CurseForge: {}/{}", project_id, file_id),
+                        _ => modinfo.url.clone()
+                    };
+                    let name = modinfo.name.clone();
+                    let (is_first, is_last) = (i == 0, i + 1 == mod_count);
+                    let (up, down, remove) = (draft.clone(), draft.clone(), draft.clone());
+                    rsx! {
+                        div {
+                            key: "{i}-{name}",
+                            class: "flex flex-row bg-slate-900 rounded-xl p-3 gap-3",
+                            div {
+                                class: "flex-1 flex flex-col overflow-hidden",
+                                p { class: "text-2xl font-bold", "{name}" },
+                                p { class: "text-base text-slate-500 truncate", "{source}" }
+                            },
+                            button {
+                                class: "bg-slate-700 hover:bg-slate-600 rounded-xl p-3 disabled:opacity-30",
+                                disabled: "{is_first}",
+                                onclick: move |_| up.with_mut(|profile| if i > 0 { profile.mods.swap(i - 1, i) }),
+                                img {
+                                    src: "https://tallie.dev/modtool/assets/fa-arrow-up.svg",
+                                    height: "24",
+                                    width: "24",
+                                    class: "mx-auto fill-slate-100"
+                                }
+                            },
+                            button {
+                                class: "bg-slate-700 hover:bg-slate-600 rounded-xl p-3 disabled:opacity-30",
+                                disabled: "{is_last}",
+                                onclick: move |_| down.with_mut(|profile| if i + 1 < profile.mods.len() { profile.mods.swap(i, i + 1) }),
+                                img {
+                                    src: "https://tallie.dev/modtool/assets/fa-arrow-down.svg",
+                                    height: "24",
+                                    width: "24",
+                                    class: "mx-auto fill-slate-100"
+                                }
+                            },
+                            button {
+                                class: "bg-red-500 hover:bg-red-700 rounded-xl p-3",
+                                onclick: move |_| remove.with_mut(|profile| { profile.mods.remove(i); }),
+                                img {
+                                    src: "https://tallie.dev/modtool/assets/fa-xmark.svg",
+                                    height: "24",
+                                    width: "24",
+                                    class: "mx-auto fill-slate-100"
+                                }
+                            }
+                        }
+                    }
+                })
+            },
+            div {
+                class: "flex flex-col my-auto",
+                button {
+                    onclick: move |_| {
+                        let mut profile = draft.get().clone();
+                        profile.meta.name = profile.meta.name.trim().to_string();
+                        if profile.meta.name.is_empty() || profile.meta.version.is_empty() {
+                            message.set(String::from("The profile needs a name and a Minecraft version"));
+                            return
+                        }
+                        if original.is_none() {
+                            profile.meta.id = match manifest::next_local_id() {
+                                Ok(id) => id,
+                                Err(err) => {
+                                    message.set(err.to_string());
+                                    return
+                                }
+                            };
+                        }
+                        if let Err(err) = manifest::save_local_profile(profile.clone()) {
+                            message.set(err.to_string());
+                            return
+                        }
+                        let mut state_cpy = state.clone();
+                        state_cpy.manifest.profiles.retain(|existing| existing.meta.id != profile.meta.id);
+                        state_cpy.selected_profile = profile.meta.id;
+                        state_cpy.manifest.profiles.push(profile);
+                        state_cpy.page = Page::ProfilePage;
+                        atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-green-700 bg-green-500 rounded-xl p-6 m-6 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-floppy-disk.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                },
+                state.editing.map(|id| rsx! {
+                    button {
+                        onclick: move |_| {
+                            if let Err(err) = manifest::delete_local_profile(id) {
+                                message.set(err.to_string());
+                                return
+                            }
+                            let mut state_cpy = state.clone();
+                            state_cpy.manifest.profiles.retain(|existing| existing.meta.id != id);
+                            if let Some(profile) = state_cpy.manifest.profiles.first() {
+                                state_cpy.selected_profile = profile.meta.id;
+                            }
+                            state_cpy.page = Page::ProfilePage;
+                            atoms.set(STATE.unique_id(), state_cpy);
+                        },
+                        class: "hover:bg-red-700 bg-red-500 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                        img {
+                          src: "https://tallie.dev/modtool/assets/fa-trash.svg",
+                          height: "32",
+                          width: "32",
+                          class: "mx-auto fill-slate-100"
+                        }
+                    }
+                }),
+                button {
+                    onclick: move |_| {
+                        let mut state_cpy = state.clone();
+                        state_cpy.page = Page::ProfilePage;
+                        atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn load_launcher_entries() -> std::result::Result<Vec<(String, launcher::LauncherProfile)>, String> {
+    launcher::load()
+        .map(|profiles| launcher::modtool_profiles(&profiles))
+        .map_err(|err| err.to_string())
+}
+
+fn LauncherProfilesPage(cx: Scope) -> Element {
+    let state = use_read(&cx, STATE);
+    let atoms = use_atom_root(&cx);
+    let entries = use_state(&cx, load_launcher_entries);
+    let selected = use_state(&cx, || None::<String>);
+    let name = use_state(&cx, String::new);
+    let version = use_state(&cx, String::new);
+    let java_args = use_state(&cx, String::new);
+    let memory = use_state(&cx, String::new);
+    let message = use_state(&cx, String::new);
+
+    let (list, load_error) = match entries.get() {
+        Ok(list) => (list.clone(), String::new()),
+        Err(err) => (Vec::new(), err.clone())
+    };
+    let current = selected.get().as_ref().and_then(|key| list.iter().find(|(existing, _)| existing == key).cloned());
+    let has_entries = !list.is_empty();
+    // What a reinstall would set lastVersionId to
+    let installed_version = current.as_ref()
+        .and_then(|(key, _)| launcher::profile_id(key))
+        .and_then(|id| state.manifest.profiles.iter().find(|profile| profile.meta.id == id).cloned())
+        .and_then(|profile| install::installed_loader(&profile.meta).ok().flatten())
+        .map(|(name, _)| name);
+
+    cx.render(rsx! {
+        div {
+            id: "launcherprofiles",
+            class: "flex flex-row flex-1 h-full",
+            div {
+                class: "flex-1 rounded-xl text-slate-100 m-6 flex flex-col p-6 gap-6 overflow-y-auto",
+                h2 {
+                    class: "text-6xl font-bold",
+                    "Launcher profiles"
+                },
+                p {
+                    class: "text-xl text-orange-600 font-bold",
+                    "{load_error}"
+                },
+                (!has_entries && load_error.is_empty()).then(|| rsx! {
+                    p {
+                        class: "text-xl text-slate-500 italic",
+                        "ModTool hasn't added any profiles to the launcher yet."
+                    }
+                }),
+                list.iter().map(|(key, profile)| {
+                    let key = key.clone();
+                    let title = profile.name.clone().unwrap_or(key.clone());
+                    let last_version = profile.lastVersionId.clone().unwrap_or_default();
+                    let missing = !launcher::version_exists(profile);
+                    let class = match selected.get().as_ref() == Some(&key) {
+                        true => "flex flex-col bg-slate-700 rounded-xl p-3 text-left",
+                        false => "flex flex-col bg-slate-900 hover:bg-slate-800 rounded-xl p-3 text-left"
+                    };
+                    let profile = profile.clone();
+                    let select_key = key.clone();
+                    let (selected, name, version, java_args, memory, message) = (selected.clone(), name.clone(), version.clone(), java_args.clone(), memory.clone(), message.clone());
+                    rsx! {
+                        button {
+                            key: "{key}",
+                            class: "{class}",
+                            onclick: move |_| {
+                                let args = profile.javaArgs.clone().unwrap_or_default();
+                                name.set(profile.name.clone().unwrap_or_default());
+                                version.set(profile.lastVersionId.clone().unwrap_or_default());
+                                memory.set(launcher::max_memory_mb(args.as_str()).map(|mb| mb.to_string()).unwrap_or_default());
+                                java_args.set(args);
+                                message.set(String::new());
+                                selected.set(Some(select_key.clone()));
+                            },
+                            div {
+                                class: "flex flex-row gap-3",
+                                p { class: "text-2xl font-bold", "{title}" },
+                                missing.then(|| rsx! {
+                                    span {
+                                        class: "text-sm font-bold text-red-400 border border-red-400 rounded px-2 my-auto",
+                                        "MISSING VERSION"
+                                    }
+                                })
+                            },
+                            p { class: "text-base text-slate-500 truncate", "{key} → {last_version}" }
+                        }
+                    }
+                }),
+                current.as_ref().map(|_| rsx! {
+                    div {
+                        class: "flex flex-col gap-6",
+                        input {
+                            class: "bg-slate-900 rounded-xl p-6 text-2xl",
+                            value: "{name}",
+                            placeholder: "Name in the launcher",
+                            oninput: move |evt| name.set(evt.value.clone())
+                        },
+                        div {
+                            class: "flex flex-row gap-6",
+                            input {
+                                class: "flex-1 bg-slate-900 rounded-xl p-6 text-2xl",
+                                value: "{version}",
+                                placeholder: "Version folder, e.g. fabric-loader-0.14.8-1.19",
+                                oninput: move |evt| version.set(evt.value.trim().to_string())
+                            },
+                            installed_version.clone().map(|installed| rsx! {
+                                button {
+                                    class: "bg-cyan-700 hover:bg-cyan-800 rounded-xl p-6 text-2xl font-bold",
+                                    onclick: move |_| version.set(installed.clone()),
+                                    "Use installed"
+                                }
+                            })
+                        },
+                        div {
+                            class: "flex flex-row gap-6",
+                            input {
+                                class: "flex-1 bg-slate-900 rounded-xl p-6 text-2xl",
+                                value: "{java_args}",
+                                placeholder: "Java arguments",
+                                oninput: move |evt| java_args.set(evt.value.clone())
+                            },
+                            input {
+                                class: "w-48 bg-slate-900 rounded-xl p-6 text-2xl",
+                                value: "{memory}",
+                                placeholder: "Memory (MB)",
+                                oninput: move |evt| memory.set(evt.value.trim().to_string())
+                            }
+                        },
+                        p {
+                            class: "text-xl text-orange-600 font-bold",
+                            "{message}"
+                        }
+                    }
+                })
+            },
+            div {
+                class: "flex flex-col my-auto",
+                current.clone().map(|(key, profile)| {
+                    let delete_key = key.clone();
+                    rsx! {
+                        button {
+                            onclick: move |_| {
+                                let mut profile = profile.clone();
+                                let mut args = java_args.get().trim().to_string();
+                                if !memory.get().is_empty() {
+                                    match memory.get().parse::<u64>() {
+                                        Ok(mb) => args = launcher::with_max_memory(args.as_str(), mb),
+                                        Err(_) => {
+                                            message.set(format!("'{}' isn't a number of megabytes", memory.get()));
+                                            return
+                                        }
+                                    }
+                                }
+                                profile.name = Some(name.get().trim().to_string());
+                                profile.lastVersionId = Some(version.get().clone());
+                                profile.javaArgs = match args.is_empty() {
+                                    true => None,
+                                    false => Some(args.clone())
+                                };
+                                match launcher::update_profile(key.as_str(), profile) {
+                                    Ok(()) => {
+                                        java_args.set(args);
+                                        message.set(String::from("Saved"));
+                                        entries.set(load_launcher_entries());
+                                    },
+                                    Err(err) => message.set(err.to_string())
+                                }
+                            },
+                            class: "hover:bg-green-700 bg-green-500 rounded-xl p-6 m-6 grow-0 flex-0 shrink",
+                            img {
+                              src: "https://tallie.dev/modtool/assets/fa-floppy-disk.svg",
+                              height: "32",
+                              width: "32",
+                              class: "mx-auto fill-slate-100"
+                            }
+                        },
+                        button {
+                            onclick: move |_| {
+                                match launcher::remove_profile(delete_key.as_str()) {
+                                    Ok(()) => {
+                                        selected.set(None);
+                                        message.set(String::new());
+                                        entries.set(load_launcher_entries());
+                                    },
+                                    Err(err) => message.set(err.to_string())
+                                }
+                            },
+                            class: "hover:bg-red-700 bg-red-500 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                            img {
+                              src: "https://tallie.dev/modtool/assets/fa-trash.svg",
+                              height: "32",
+                              width: "32",
+                              class: "mx-auto fill-slate-100"
+                            }
+                        }
+                    }
+                }),
+                button {
+                    onclick: move |_| {
+                        let mut state_cpy = state.clone();
+                        state_cpy.page = Page::ProfilePage;
+                        atoms.set(STATE.unique_id(), state_cpy);
+                    },
+                    class: "hover:bg-slate-600 bg-slate-700 rounded-xl p-6 m-6 mt-0 grow-0 flex-0 shrink",
+                    img {
+                      src: "https://tallie.dev/modtool/assets/fa-arrow-left.svg",
+                      height: "32",
+                      width: "32",
+                      class: "mx-auto fill-slate-100"
+                    }
+                }
+            }
+        }
+    })
+}
+
 fn ManifestPage(cx: Scope) -> Element {
     let atoms = use_atom_root(&cx);
 
     use_future(&cx, (), |_| manifest_download_handler(atoms.clone()));
-    cx.spawn(
-        manifest_download_handler(atoms.clone())
-    );
 
     cx.render(rsx! {
         div {
@@ -1444,28 +2893,51 @@ fn ManifestPage(cx: Scope) -> Element {
 }
 
 async fn manifest_download_handler(ar: Rc<AtomRoot>) {
-    let manifest_txt = reqwest::get("https://tallie.dev/modtool/manifest.json").await.unwrap()
-        .text().await.unwrap();
-    let manifest: Manifest = serde_json::from_str(manifest_txt.as_str()).unwrap();
-
-    let mut new_manifest = Manifest {
-        profiles: Vec::new()
+    let manifest = match manifest::fetch_manifest().await {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            show_error(&ar, err, Page::ManifestDownloadPage, None);
+            return
+        }
     };
 
-    new_manifest.profiles.push(Profile {
-        meta: ProfileMeta {
-            name: String::from("Test"),
-            version: String::from("1.16.5"),
-            loader: ModLoader::Fabric,
-            id: 0
-        },
-        mods: Vec::new()
-    });
-
-
     let mut state_cpy: AppState = (*ar.read(STATE)).clone();
     state_cpy.page = Page::HomePage;
     state_cpy.manifest = manifest.clone();
     ar.set(STATE.unique_id(), state_cpy);
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mod_entries_become_manifest_mods() {
+        let url = parse_mod_entry(" https://cdn.modrinth.com/data/AANobbMI/versions/1/sodium-0.4.10.jar ").unwrap();
+        assert_eq!(url.name, "sodium-0.4.10");
+        assert_eq!(url.url, "https://cdn.modrinth.com/data/AANobbMI/versions/1/sodium-0.4.10.jar");
+        assert!(url.provider == ModProvider::Modrinth);
+
+        let curseforge = parse_mod_entry("238222/4371807").unwrap();
+        assert!(curseforge.provider == ModProvider::CurseForge);
+        assert_eq!((curseforge.projectID, curseforge.fileID), (Some(238222), Some(4371807)));
+        assert!(curseforge.url.is_empty());
+
+        let slug = parse_mod_entry("lithium").unwrap();
+        assert!(slug.provider == ModProvider::Modrinth);
+        assert_eq!(slug.project.as_deref(), Some("lithium"));
+    }
+
+    #[test]
+    fn incomplete_or_malformed_entries_are_rejected() {
+        assert!(parse_mod_entry("").is_none());
+        assert!(parse_mod_entry("   ").is_none());
+        assert!(parse_mod_entry("https://example.com/mods/").is_none());
+        assert!(parse_mod_entry("238222/").is_none());
+        assert!(parse_mod_entry("/4371807").is_none());
+        assert!(parse_mod_entry("jei/latest").is_none());
+        assert!(parse_mod_entry("238222/4371807/extra").is_none());
+        assert!(parse_mod_entry("-1/4371807").is_none());
+    }
+}
@@ -0,0 +1,190 @@
+// Loading and merging manifests from the sources listed in the settings
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::settings::settings;
+use std::path::PathBuf;
+
+// Profiles imported or made on this machine are kept in CONFIG_DIR/local_manifest.json. Their ids start here,
+// well clear of the remote manifests, so they never collide with them.
+static LOCAL_ID_START: i32 = 1000000;
+
+// Profiles from the default manifest keep their ids. Every other source gets a block of ids of its own,
+// picked from a hash of where it's loaded from, so adding, removing or reordering sources never moves a
+// profile onto another one's game directory, install record or launcher entry. Blocks below
+// FIRST_SOURCE_BLOCK belong to the default manifest (0) and local profiles.
+static SOURCE_BLOCK_SIZE: i32 = 1000000;
+static FIRST_SOURCE_BLOCK: u32 = 10;
+static SOURCE_BLOCKS: u32 = 2000;
+
+impl Manifest {
+    // Appends the profiles of `other`, with `offset` added to their ids. A profile whose id is still
+    // taken is left out rather than renumbered, since its id decides where it gets installed.
+    pub fn merge(&mut self, other: Manifest, offset: i32) {
+        for mut profile in other.profiles {
+            if offset != 0 && !(0..SOURCE_BLOCK_SIZE).contains(&profile.meta.id) {
+                log::warn!("Skipping profile {}: its id {} doesn't fit in its manifest's id block", profile.meta.name, profile.meta.id);
+                continue
+            }
+            profile.meta.id += offset;
+            if let Some(existing) = self.profiles.iter().find(|existing| existing.meta.id == profile.meta.id) {
+                log::warn!("Skipping profile {}: id {} is already used by {}", profile.meta.name, profile.meta.id, existing.meta.name);
+                continue
+            }
+            self.profiles.push(profile);
+        }
+    }
+
+    pub fn next_id(&self) -> i32 {
+        self.profiles.iter().map(|profile| profile.meta.id + 1).max().unwrap_or(0)
+    }
+}
+
+// What's added to the ids of the profiles from `source`
+pub fn source_id_offset(source: &str) -> i32 {
+    let source = source.trim();
+    if source == settings::DEFAULT_MANIFEST {
+        return 0
+    }
+    let hash = u32::from_str_radix(&hashes::sha256_hex(source.as_bytes())[..8], 16).unwrap_or(0);
+    (FIRST_SOURCE_BLOCK + hash % SOURCE_BLOCKS) as i32 * SOURCE_BLOCK_SIZE
+}
+
+pub fn local_manifest_path() -> PathBuf {
+    Path::new(CONFIG_DIR.as_str()).join("local_manifest.json")
+}
+
+pub fn load_local() -> Result<Manifest> {
+    let path = local_manifest_path();
+    if !path.exists() {
+        return Ok(Manifest {
+            profiles: Vec::new()
+        })
+    }
+    let data = std::fs::read_to_string(&path).map_err(|err| ModtoolError::io(&path, err))?;
+    Ok(serde_json::from_str(data.as_str())?)
+}
+
+pub fn save_local(manifest: &Manifest) -> Result<()> {
+    let path = local_manifest_path();
+    std::fs::write(&path, serde_json::to_string_pretty(manifest)?).map_err(|err| ModtoolError::io(&path, err))
+}
+
+pub fn is_local(id: i32) -> bool {
+    id >= LOCAL_ID_START
+}
+
+pub fn next_local_id() -> Result<i32> {
+    Ok(load_local()?.next_id().max(LOCAL_ID_START))
+}
+
+// Saves a profile to the local manifest, replacing any with the same id
+pub fn save_local_profile(profile: Profile) -> Result<()> {
+    let mut local = load_local()?;
+    local.profiles.retain(|existing| existing.meta.id != profile.meta.id);
+    local.profiles.push(profile);
+    save_local(&local)
+}
+
+pub fn delete_local_profile(id: i32) -> Result<()> {
+    let mut local = load_local()?;
+    local.profiles.retain(|existing| existing.meta.id != id);
+    save_local(&local)
+}
+
+async fn read_source(source: &str) -> Result<String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        Ok(HTTP_CLIENT
+            .get(source)
+            .header("User-Agent", format!("Starkiller645/modtool-rs/{APP_VERSION} (tallie@tallie.dev)"))
+            .send().await?
+            .error_for_status()?
+            .text().await?)
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        std::fs::read_to_string(path).map_err(|err| ModtoolError::io(path, err))
+    }
+}
+
+pub async fn fetch_source(source: &str) -> Result<Manifest> {
+    let manifest_txt = read_source(source).await?;
+    Ok(serde_json::from_str(manifest_txt.as_str())?)
+}
+
+// Merges every configured source, then the local manifest. Sources that fail are skipped,
+// unless none of them load and there are no local profiles either.
+pub async fn fetch_manifest() -> Result<Manifest> {
+    let sources = settings().manifest_sources;
+    let mut manifest = Manifest {
+        profiles: Vec::new()
+    };
+    let mut errors = Vec::new();
+    for source in sources.iter() {
+        match fetch_source(source.as_str()).await {
+            Ok(other) => manifest.merge(other, source_id_offset(source.as_str())),
+            Err(err) => {
+                log::warn!("Could not load manifest {}: {}", source, err);
+                errors.push(err);
+            }
+        }
+    }
+    let local = match load_local() {
+        Ok(local) => local,
+        Err(err) => {
+            log::warn!("Could not load {}: {}", local_manifest_path().display(), err);
+            Manifest {
+                profiles: Vec::new()
+            }
+        }
+    };
+    let has_local = !local.profiles.is_empty();
+    manifest.merge(local, 0);
+    if errors.len() == sources.len() && !has_local {
+        return Err(match errors.pop() {
+            Some(err) if errors.is_empty() => err,
+            Some(_) => ModtoolError::Manifest(format!("None of the {} manifest sources could be loaded", sources.len())),
+            None => ModtoolError::Manifest(String::from("No manifest sources are configured"))
+        })
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(ids: &[i32]) -> Manifest {
+        let profiles = ids.iter().map(|id| serde_json::from_value(serde_json::json!({
+            "meta": { "name": format!("Profile {}", id), "loader": "Fabric", "version": "1.19.2", "id": id },
+            "mods": []
+        })).unwrap()).collect();
+        Manifest { profiles }
+    }
+
+    fn merged(sources: &[(&str, &[i32])]) -> Vec<i32> {
+        let mut merged = manifest(&[]);
+        for (source, ids) in sources {
+            merged.merge(manifest(ids), source_id_offset(source));
+        }
+        let mut ids: Vec<i32> = merged.profiles.iter().map(|profile| profile.meta.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn ids_dont_depend_on_source_order() {
+        let a: (&str, &[i32]) = ("https://example.com/a.json", &[0, 1]);
+        let b: (&str, &[i32]) = ("/home/user/b.json", &[0, 1]);
+        let default: (&str, &[i32]) = (settings::DEFAULT_MANIFEST, &[0, 1]);
+        assert_eq!(merged(&[default, a, b]), merged(&[b, a, default]));
+        assert_eq!(&merged(&[a, default])[..2], &[0, 1]);
+    }
+
+    #[test]
+    fn taken_ids_are_skipped_not_renumbered() {
+        let mut merged = manifest(&[3]);
+        merged.merge(manifest(&[3, 4]), 0);
+        let ids: Vec<i32> = merged.profiles.iter().map(|profile| profile.meta.id).collect();
+        assert_eq!(ids, vec![3, 4]);
+    }
+}
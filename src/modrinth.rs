@@ -0,0 +1,153 @@
+// Looks up manifest entries like `{"provider": "Modrinth", "project": "sodium"}` in the Modrinth v2
+// API, picking the newest version for the profile's Minecraft version and loader.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::settings::settings;
+
+#[derive(Deserialize)]
+struct Version {
+    version_number: String,
+    date_published: String,
+    files: Vec<VersionFile>
+}
+
+#[derive(Deserialize)]
+struct VersionFile {
+    url: String,
+    filename: String,
+    size: i64,
+    #[serde(default)]
+    primary: bool,
+    hashes: FileHashes
+}
+
+#[derive(Deserialize)]
+struct FileHashes {
+    sha1: Option<String>,
+    sha512: Option<String>
+}
+
+// Quilt can load Fabric mods too
+fn loader_names(loader: ModLoader) -> &'static [&'static str] {
+    match loader {
+        ModLoader::Fabric => &["fabric"],
+        ModLoader::Forge => &["forge"],
+        ModLoader::Quilt => &["quilt", "fabric"],
+        ModLoader::NeoForge => &["neoforge"]
+    }
+}
+
+async fn project_versions(project: &str, meta: &ProfileMeta) -> Result<Vec<Version>> {
+    let url = format!("{}/project/{}/version", settings().modrinth_api.trim_end_matches('/'), project);
+    let res = HTTP_CLIENT
+        .get(url)
+        .query(&[
+            ("loaders", serde_json::to_string(loader_names(meta.loader)).unwrap()),
+            ("game_versions", format!("[\"{}\"]", meta.version))
+        ])
+        .header("User-Agent", format!("Starkiller645/modtool-rs/{APP_VERSION} (tallie@tallie.dev)"))
+        .send()
+        .await?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(ModtoolError::Manifest(format!("There is no Modrinth project called '{}'", project)))
+    }
+    Ok(res.error_for_status()?.json().await?)
+}
+
+// Returns a copy of `modinfo` with the url, filename, size and hashes of the file to download.
+// A `version` in the manifest pins that version number, otherwise the newest one is used.
+pub async fn resolve(modinfo: &Mod, meta: &ProfileMeta) -> Result<Mod> {
+    let project = match modinfo.project.as_ref() {
+        Some(project) => project,
+        None => return Err(ModtoolError::Manifest(format!("{} has neither a url nor a Modrinth project", modinfo.name)))
+    };
+    let mut versions = project_versions(project.as_str(), meta).await?;
+    // RFC 3339 dates sort as strings, newest first
+    versions.sort_by(|a, b| b.date_published.cmp(&a.date_published));
+
+    let version = match modinfo.version.is_empty() {
+        true => versions.into_iter().next(),
+        false => versions.into_iter().find(|version| version.version_number == modinfo.version)
+    };
+    let version = match version {
+        Some(version) => version,
+        None => return Err(ModtoolError::Manifest(format!("Modrinth has no {} {} for {} {}",
+            project, modinfo.version, loader_names(meta.loader)[0], meta.version)))
+    };
+
+    let version_number = version.version_number;
+    let mut files = version.files;
+    let position = files.iter().position(|file| file.primary).unwrap_or(0);
+    if files.is_empty() {
+        return Err(ModtoolError::Manifest(format!("Modrinth version {} of {} has no files", version_number, project)))
+    }
+    let file = files.swap_remove(position);
+
+    let mut resolved = modinfo.clone();
+    resolved.url = file.url;
+    resolved.filename = Some(file.filename);
+    resolved.version = version_number;
+    resolved.size = file.size as i32;
+    resolved.sha1 = file.hashes.sha1;
+    resolved.sha512 = file.hashes.sha512;
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockServer};
+
+    fn version(number: &str, date: &str, files: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "version_number": number, "date_published": date, "files": files })
+    }
+
+    fn file(name: &str, primary: bool) -> serde_json::Value {
+        serde_json::json!({
+            "url": format!("https://cdn.example.com/{}", name), "filename": name, "size": 1234, "primary": primary,
+            "hashes": { "sha1": format!("sha1-{}", name), "sha512": null }
+        })
+    }
+
+    async fn server() -> MockServer {
+        test_support::home();
+        let versions = serde_json::json!([
+            version("0.4.0", "2022-08-01T00:00:00Z", serde_json::json!([file("old.jar", true)])),
+            version("0.5.0", "2022-09-01T00:00:00Z", serde_json::json!([file("sources.jar", false), file("new.jar", true)])),
+            version("0.4.5", "2022-08-15T00:00:00Z", serde_json::json!([file("mid.jar", true)]))
+        ]);
+        let server = MockServer::start(vec![("/project/sodium/version", 200, MockServer::json(versions))]).await;
+        settings::SETTINGS.write().unwrap().modrinth_api = server.url.clone();
+        server
+    }
+
+    fn entry(project: &str, version: &str) -> (Mod, ProfileMeta) {
+        let modinfo = serde_json::from_value(serde_json::json!({ "name": project, "provider": "Modrinth", "project": project, "version": version })).unwrap();
+        let meta = serde_json::from_value(serde_json::json!({ "name": "Test", "loader": "Fabric", "version": "1.19.2", "id": 1 })).unwrap();
+        (modinfo, meta)
+    }
+
+    // One test, since they'd all point `modrinth_api` at their own server
+    #[tokio::test]
+    async fn resolves_against_a_local_server() {
+        let _globals = test_support::lock_globals().await;
+        let server = server().await;
+
+        let (modinfo, meta) = entry("sodium", "");
+        let resolved = resolve(&modinfo, &meta).await.unwrap();
+        assert_eq!(resolved.version, "0.5.0");
+        assert_eq!(resolved.filename.as_deref(), Some("new.jar"));
+        assert_eq!(resolved.sha1.as_deref(), Some("sha1-new.jar"));
+        assert_eq!(server.requests.lock().unwrap().as_slice(), ["/project/sodium/version"]);
+
+        let (modinfo, meta) = entry("sodium", "0.4.0");
+        assert_eq!(resolve(&modinfo, &meta).await.unwrap().filename.as_deref(), Some("old.jar"));
+
+        let (modinfo, meta) = entry("sodium", "9.9.9");
+        assert!(matches!(resolve(&modinfo, &meta).await, Err(ModtoolError::Manifest(message)) if message.contains("no sodium 9.9.9")));
+
+        let (modinfo, meta) = entry("missing", "");
+        assert!(matches!(resolve(&modinfo, &meta).await, Err(ModtoolError::Manifest(message)) if message.contains("no Modrinth project called 'missing'")));
+    }
+}
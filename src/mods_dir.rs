@@ -0,0 +1,435 @@
+// Keeps track of which files in a mods folder were written by modtool, so installing a
+// profile only removes those, and only downloads the mods that changed since last time.
+// Anything else in the folder is backed up to a zip in CACHE_DIR/backups first, and can
+// be restored from there.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::hashes::file_sha1;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use zip::write::FileOptions;
+
+lazy_static! {
+    // Mods download concurrently, and each one adds itself to the install record when it's done
+    static ref RECORD_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// Written to CONFIG_DIR/installs/<profile id>.json when a profile is installed
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InstallRecord {
+    pub profile_id: i32,
+    pub profile_name: String,
+    pub mods_dir: String,
+    pub files: Vec<InstalledFile>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct InstalledFile {
+    pub name: String,
+    pub filename: String,
+    pub version: String,
+    pub sha1: String,
+    // Where the jar was downloaded from, empty for mods that had to be downloaded by hand
+    #[serde(default)]
+    pub url: String,
+    // Installed to satisfy another mod's dependency, so it's kept even though the manifest doesn't list it
+    #[serde(default)]
+    pub dependency: bool
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize
+}
+
+pub struct SyncPlan {
+    pub backup: Option<PathBuf>,
+    // Urls of the mods that are already installed and don't need downloading
+    pub unchanged: Vec<String>,
+    pub summary: SyncSummary
+}
+
+impl InstallRecord {
+    fn dir() -> PathBuf {
+        Path::new(CONFIG_DIR.as_str()).join("installs")
+    }
+
+    fn path(profile_id: i32) -> PathBuf {
+        InstallRecord::dir().join(format!("{}.json", profile_id))
+    }
+
+    pub fn load(profile_id: i32) -> Result<Option<InstallRecord>> {
+        let path = InstallRecord::path(profile_id);
+        if !path.exists() {
+            return Ok(None)
+        }
+        let data = std::fs::read_to_string(&path).map_err(|err| ModtoolError::io(&path, err))?;
+        Ok(Some(serde_json::from_str(data.as_str())?))
+    }
+
+    pub fn all() -> Result<Vec<InstallRecord>> {
+        let dir = InstallRecord::dir();
+        let mut records = Vec::new();
+        if !dir.exists() {
+            return Ok(records)
+        }
+        for entry in std::fs::read_dir(&dir).map_err(|err| ModtoolError::io(&dir, err))? {
+            let path = entry.map_err(|err| ModtoolError::io(&dir, err))?.path();
+            if path.extension().map(|ext| ext != "json").unwrap_or(true) {
+                continue
+            }
+            let data = std::fs::read_to_string(&path).map_err(|err| ModtoolError::io(&path, err))?;
+            records.push(serde_json::from_str(data.as_str())?);
+        }
+        Ok(records)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = InstallRecord::dir();
+        std::fs::create_dir_all(&dir).map_err(|err| ModtoolError::io(&dir, err))?;
+        let path = InstallRecord::path(self.profile_id);
+        // Written next to the record and renamed over it, so it's never read half written
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?).map_err(|err| ModtoolError::io(&tmp_path, err))?;
+        std::fs::rename(&tmp_path, &path).map_err(|err| ModtoolError::io(&path, err))
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        let path = InstallRecord::path(self.profile_id);
+        std::fs::remove_file(&path).map_err(|err| ModtoolError::io(&path, err))
+    }
+}
+
+fn recorded_files(mods_dir: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for record in InstallRecord::all()? {
+        if record.mods_dir == mods_dir {
+            files.extend(record.files.iter().map(|file| PathBuf::from(&file.filename)));
+        }
+    }
+    Ok(files)
+}
+
+// Removes every file that an earlier modtool install wrote into `mods_dir`, and forgets those
+// installs. The install record of `keep` is left alone.
+fn remove_recorded_files(mods_dir: &str, keep: Option<i32>) -> Result<()> {
+    for record in InstallRecord::all()? {
+        if record.mods_dir != mods_dir || Some(record.profile_id) == keep {
+            continue
+        }
+        for file in record.files.iter() {
+            let path = Path::new(mods_dir).join(&file.filename);
+            if path.exists() {
+                std::fs::remove_file(&path).map_err(|err| ModtoolError::io(&path, err))?;
+            }
+        }
+        record.delete()?;
+    }
+    Ok(())
+}
+
+pub fn backups_dir() -> PathBuf {
+    Path::new(CACHE_DIR.as_str()).join("backups")
+}
+
+// Zips up the files in `mods_dir` that modtool didn't install (leaving out unfinished downloads), since
+// the rest can be downloaded again. Returns the archive path, or None if there's nothing to keep or the
+// newest backup of the folder already holds exactly these files.
+pub fn backup_mods_dir(mods_dir: &str) -> Result<Option<PathBuf>> {
+    let mut files = Vec::new();
+    collect_files(Path::new(mods_dir), Path::new(""), &mut files)?;
+    let recorded = recorded_files(mods_dir)?;
+    files.retain(|file| !recorded.contains(file) && file.extension().map(|ext| ext != "part").unwrap_or(true));
+    if files.is_empty() || matches_last_backup(mods_dir, &files) {
+        return Ok(None)
+    }
+
+    let dir = backups_dir();
+    std::fs::create_dir_all(&dir).map_err(|err| ModtoolError::io(&dir, err))?;
+    let timestamp = Utc::now().format("%Y-%m-%d_%H-%M-%S");
+    let path = dir.join(format!("mods-{}.zip", timestamp));
+    let fhandle = File::create(&path).map_err(|err| ModtoolError::io(&path, err))?;
+
+    let mut archive = zip::ZipWriter::new(fhandle);
+    // The comment remembers which folder to restore into
+    archive.set_comment(mods_dir);
+    for relative in files {
+        let name = relative.to_string_lossy().replace('\\', "/");
+        archive.start_file(name, FileOptions::default())?;
+        let source = Path::new(mods_dir).join(&relative);
+        let mut input = File::open(&source).map_err(|err| ModtoolError::io(&source, err))?;
+        std::io::copy(&mut input, &mut archive).map_err(|err| ModtoolError::io(&path, err))?;
+    }
+    archive.finish()?;
+    Ok(Some(path))
+}
+
+// Compares names, sizes and CRC32s against the newest backup taken of `mods_dir`. A backup that
+// can't be read counts as different, so a new one is made.
+fn matches_last_backup(mods_dir: &str, files: &[PathBuf]) -> bool {
+    let backups = match list_backups() {
+        Ok(backups) => backups,
+        Err(_) => return false
+    };
+    for backup in backups {
+        let mut archive = match File::open(&backup).ok().and_then(|fhandle| zip::ZipArchive::new(fhandle).ok()) {
+            Some(archive) => archive,
+            None => continue
+        };
+        if archive.comment() != mods_dir.as_bytes() {
+            continue
+        }
+        if archive.len() != files.len() {
+            return false
+        }
+        return files.iter().all(|relative| {
+            let name = relative.to_string_lossy().replace('\\', "/");
+            let path = Path::new(mods_dir).join(relative);
+            match (archive.by_name(name.as_str()), std::fs::metadata(&path), hashes::file_crc32(&path)) {
+                (Ok(entry), Ok(metadata), Ok(crc32)) => entry.size() == metadata.len() && entry.crc32() == crc32,
+                _ => false
+            }
+        })
+    }
+    false
+}
+
+pub fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let dir = root.join(relative);
+    if !dir.exists() {
+        return Ok(())
+    }
+    for entry in std::fs::read_dir(&dir).map_err(|err| ModtoolError::io(&dir, err))? {
+        let entry = entry.map_err(|err| ModtoolError::io(&dir, err))?;
+        let child = relative.join(entry.file_name());
+        if entry.path().is_dir() {
+            collect_files(root, &child, files)?;
+        } else {
+            files.push(child);
+        }
+    }
+    Ok(())
+}
+
+// Newest first
+pub fn list_backups() -> Result<Vec<PathBuf>> {
+    let dir = backups_dir();
+    let mut backups = Vec::new();
+    if !dir.exists() {
+        return Ok(backups)
+    }
+    for entry in std::fs::read_dir(&dir).map_err(|err| ModtoolError::io(&dir, err))? {
+        let path = entry.map_err(|err| ModtoolError::io(&dir, err))?.path();
+        if path.extension().map(|ext| ext == "zip").unwrap_or(false) {
+            backups.push(path);
+        }
+    }
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+// Takes out the files modtool installed and puts the backed up ones back into the folder they came from.
+// Returns how many files were restored, and where to.
+pub fn restore_backup(backup: &Path) -> Result<(usize, String)> {
+    let fhandle = File::open(backup).map_err(|err| ModtoolError::io(backup, err))?;
+    let mut archive = zip::ZipArchive::new(fhandle)?;
+    let mods_dir = match String::from_utf8_lossy(archive.comment()).to_string() {
+        comment if !comment.is_empty() => comment,
+        _ => MC_DATA.mods_dir.clone()
+    };
+    let mods_dir = mods_dir.as_str();
+
+    remove_recorded_files(mods_dir, None)?;
+    std::fs::create_dir_all(mods_dir).map_err(|err| ModtoolError::io(mods_dir, err))?;
+
+    let mut restored = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let relative = match entry.enclosed_name() {
+            Some(relative) => relative.to_path_buf(),
+            None => continue
+        };
+        let target = Path::new(mods_dir).join(relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target).map_err(|err| ModtoolError::io(&target, err))?;
+            continue
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| ModtoolError::io(parent, err))?;
+        }
+        let mut output = File::create(&target).map_err(|err| ModtoolError::io(&target, err))?;
+        std::io::copy(&mut entry, &mut output).map_err(|err| ModtoolError::io(&target, err))?;
+        restored += 1;
+    }
+    Ok((restored, String::from(mods_dir)))
+}
+
+fn is_current(file: &InstalledFile, modinfo: &Mod, filename: &str, mods_dir: &str) -> bool {
+    if file.filename != filename || file.version != modinfo.version {
+        return false
+    }
+    if let Some(sha1) = modinfo.sha1.as_ref() {
+        if sha1.to_lowercase() != file.sha1 {
+            return false
+        }
+    }
+    match file_sha1(&Path::new(mods_dir).join(filename)) {
+        Ok(sha1) => sha1 == file.sha1,
+        Err(_) => false
+    }
+}
+
+// Run before a profile's mods are downloaded. Backs up the folder if it changed, removes whatever other modtool
+// installs put there, and compares the profile's last install against the manifest: jars that
+// were removed or changed are deleted, and the ones that are still current are left in place,
+// along with dependencies that an earlier install added.
+pub fn sync_mods_dir(profile: &Profile, mods_dir: &str) -> Result<SyncPlan> {
+    let _lock = RECORD_LOCK.lock().unwrap();
+    let backup = backup_mods_dir(mods_dir)?;
+    remove_recorded_files(mods_dir, Some(profile.meta.id))?;
+
+    let previous = match InstallRecord::load(profile.meta.id)? {
+        Some(record) if record.mods_dir == mods_dir => record.files,
+        _ => Vec::new()
+    };
+
+    let mut summary = SyncSummary::default();
+    let mut unchanged = Vec::new();
+    let mut kept: Vec<InstalledFile> = Vec::new();
+    let mut matched: Vec<String> = Vec::new();
+    for modinfo in profile.mods.iter() {
+        let filename = match install::mod_filename(&modinfo.filename, &modinfo.url) {
+            Some(filename) => filename,
+            None => continue
+        };
+        let old = previous.iter().find(|file| file.filename == filename)
+            .or_else(|| previous.iter().find(|file| file.name == modinfo.name));
+        match old {
+            Some(old) if is_current(old, modinfo, filename.as_str(), mods_dir) => {
+                summary.unchanged += 1;
+                unchanged.push(modinfo.url.clone());
+                kept.push(old.clone());
+                matched.push(old.filename.clone());
+            },
+            Some(old) => {
+                summary.updated += 1;
+                matched.push(old.filename.clone());
+            },
+            None => summary.added += 1
+        }
+    }
+
+    for old in previous.iter() {
+        if kept.contains(old) {
+            continue
+        }
+        if old.dependency && !matched.contains(&old.filename) && file_sha1(&Path::new(mods_dir).join(&old.filename)).ok() == Some(old.sha1.clone()) {
+            kept.push(old.clone());
+            continue
+        }
+        if !matched.contains(&old.filename) {
+            summary.removed += 1;
+        }
+        let path = Path::new(mods_dir).join(&old.filename);
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|err| ModtoolError::io(&path, err))?;
+        }
+    }
+
+    let record = InstallRecord {
+        profile_id: profile.meta.id,
+        profile_name: profile.meta.name.clone(),
+        mods_dir: String::from(mods_dir),
+        files: kept
+    };
+    record.save()?;
+    Ok(SyncPlan { backup, unchanged, summary })
+}
+
+// Adds a jar to the profile's install record once it has downloaded
+pub fn record_download(meta: &ProfileMeta, mods_dir: &str, modinfo: &ModDownload) -> Result<()> {
+    let filename = match install::mod_filename(&modinfo.filename, &modinfo.url) {
+        Some(filename) => filename,
+        None => return Ok(())
+    };
+    let path = Path::new(mods_dir).join(&filename);
+    let sha1 = file_sha1(&path).map_err(|err| ModtoolError::io(&path, err))?;
+
+    let _lock = RECORD_LOCK.lock().unwrap();
+    let mut record = match InstallRecord::load(meta.id)? {
+        Some(record) if record.mods_dir == mods_dir => record,
+        _ => InstallRecord {
+            profile_id: meta.id,
+            profile_name: meta.name.clone(),
+            mods_dir: String::from(mods_dir),
+            files: Vec::new()
+        }
+    };
+    record.files.retain(|file| file.filename != filename);
+    record.files.push(InstalledFile {
+        name: modinfo.name.clone(),
+        filename,
+        version: modinfo.version.clone(),
+        sha1,
+        url: match modinfo.manual {
+            true => String::new(),
+            false => modinfo.url.clone()
+        },
+        dependency: modinfo.dependency
+    });
+    record.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn backs_up_only_when_untracked_files_change() {
+        test_support::home();
+        let mods_dir = test_support::scratch_dir("backup-mods");
+        let mods_dir_str = mods_dir.to_string_lossy().to_string();
+        std::fs::write(mods_dir.join("mine.jar"), b"hand installed").unwrap();
+        std::fs::write(mods_dir.join("half.jar.part"), b"unfinished").unwrap();
+
+        let first = backup_mods_dir(mods_dir_str.as_str()).unwrap().expect("first run backs up");
+        let archive = zip::ZipArchive::new(File::open(&first).unwrap()).unwrap();
+        assert_eq!(archive.file_names().collect::<Vec<_>>(), vec!["mine.jar"]);
+        assert!(backup_mods_dir(mods_dir_str.as_str()).unwrap().is_none());
+
+        // A new backup needs a new timestamp, which has one second resolution
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        std::fs::write(mods_dir.join("mine.jar"), b"hand installed, v2").unwrap();
+        assert!(backup_mods_dir(mods_dir_str.as_str()).unwrap().is_some());
+    }
+
+    #[test]
+    fn parallel_downloads_all_get_recorded() {
+        test_support::home();
+        let mods_dir = test_support::scratch_dir("record-mods");
+        let mods_dir_str = mods_dir.to_string_lossy().to_string();
+        let meta: ProfileMeta = serde_json::from_value(serde_json::json!({ "name": "Parallel", "loader": "Fabric", "version": "1.19.2", "id": 424242 })).unwrap();
+
+        let threads: Vec<_> = (0..16).map(|i| {
+            let filename = format!("mod-{}.jar", i);
+            std::fs::write(mods_dir.join(&filename), format!("jar {}", i)).unwrap();
+            let modinfo: Mod = serde_json::from_value(serde_json::json!({
+                "name": format!("Mod {}", i), "url": format!("https://example.com/{}", filename), "provider": "Modrinth"
+            })).unwrap();
+            let (meta, mods_dir) = (meta.clone(), mods_dir_str.clone());
+            std::thread::spawn(move || record_download(&meta, mods_dir.as_str(), &install::mod_download(&modinfo)).unwrap())
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let record = InstallRecord::load(meta.id).unwrap().unwrap();
+        assert_eq!(record.files.len(), 16);
+    }
+}
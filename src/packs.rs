@@ -0,0 +1,557 @@
+// Importing modpacks from other launchers as local profiles, and exporting installed profiles
+// for them. Modrinth packs (.mrpack) list their mods with urls and hashes, CurseForge packs only
+// by project and file id, so those are looked up when the profile is installed. Either way the
+// pack's overrides are unpacked to CONFIG_DIR/packs/<profile id>/overrides, which is copied over
+// the game directory whenever the profile is installed.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+use crate::hashes::{file_sha1, file_sha512};
+use std::io::{Read, Seek};
+use std::path::{Component, PathBuf};
+use zip::ZipArchive;
+use zip::write::FileOptions;
+
+#[derive(Serialize, Deserialize)]
+struct MrpackIndex {
+    #[serde(default)]
+    formatVersion: i32,
+    #[serde(default)]
+    game: String,
+    name: String,
+    #[serde(default)]
+    versionId: String,
+    files: Vec<MrpackFile>,
+    dependencies: HashMap<String, String>
+}
+
+#[derive(Serialize, Deserialize)]
+struct MrpackFile {
+    path: String,
+    hashes: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    env: Option<MrpackEnv>,
+    downloads: Vec<String>,
+    #[serde(default)]
+    fileSize: i64
+}
+
+#[derive(Serialize, Deserialize)]
+struct MrpackEnv {
+    client: String
+}
+
+#[derive(Deserialize)]
+struct CurseForgeManifest {
+    name: String,
+    #[serde(default)]
+    version: String,
+    minecraft: CurseForgeMinecraft,
+    files: Vec<CurseForgeFile>,
+    #[serde(default = "default_overrides")]
+    overrides: String
+}
+
+fn default_overrides() -> String {
+    String::from("overrides")
+}
+
+#[derive(Deserialize)]
+struct CurseForgeMinecraft {
+    version: String,
+    modLoaders: Vec<CurseForgeLoader>
+}
+
+#[derive(Deserialize)]
+struct CurseForgeLoader {
+    // Loader and version, like "forge-43.2.0" or "fabric-0.14.21"
+    id: String,
+    #[serde(default)]
+    primary: bool
+}
+
+#[derive(Deserialize)]
+struct CurseForgeFile {
+    projectID: u64,
+    fileID: u64,
+    #[serde(default = "default_required")]
+    required: bool
+}
+
+fn default_required() -> bool {
+    true
+}
+
+pub struct ExportReport {
+    // Mods listed by url in the index
+    pub listed: usize,
+    // Mods with nowhere to download them from, which are put in the pack itself
+    pub bundled: Vec<String>,
+    pub overrides: usize
+}
+
+pub struct ImportReport {
+    pub profile: Profile,
+    // Everything in the pack that won't be installed, and why
+    pub skipped: Vec<String>,
+    // Mods that only have a CurseForge id, so they need the API (and a key) when installing
+    pub unresolved: Vec<String>
+}
+
+fn pack_dir(id: i32) -> PathBuf {
+    Path::new(CONFIG_DIR.as_str()).join("packs").join(id.to_string())
+}
+
+// Works out which provider a download url belongs to, for the label on the download page
+pub fn url_provider(url: &str) -> ModProvider {
+    if url.contains("modrinth.com/") {
+        ModProvider::Modrinth
+    } else if url.contains("forgecdn.net/") || url.contains("curseforge.com/") {
+        ModProvider::CurseForge
+    } else {
+        ModProvider::Unknown
+    }
+}
+
+// Unpacks every entry under `prefix` into `target`, keeping the folder structure below it
+fn extract_prefix<R: Read + Seek>(archive: &mut ZipArchive<R>, prefix: &str, target: &Path) -> Result<usize> {
+    let mut extracted = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let relative = match entry.enclosed_name().and_then(|name| name.strip_prefix(prefix).ok()) {
+            Some(relative) if !relative.as_os_str().is_empty() => relative.to_path_buf(),
+            _ => continue
+        };
+        let path = target.join(relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&path).map_err(|err| ModtoolError::io(&path, err))?;
+            continue
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| ModtoolError::io(parent, err))?;
+        }
+        let mut output = File::create(&path).map_err(|err| ModtoolError::io(&path, err))?;
+        std::io::copy(&mut entry, &mut output).map_err(|err| ModtoolError::io(&path, err))?;
+        extracted += 1;
+    }
+    Ok(extracted)
+}
+
+fn mrpack_loader(dependencies: &HashMap<String, String>) -> Result<(ModLoader, String)> {
+    if let Some(version) = dependencies.get("fabric-loader") {
+        return Ok((ModLoader::Fabric, version.clone()))
+    }
+    if let Some(version) = dependencies.get("forge") {
+        return Ok((ModLoader::Forge, version.clone()))
+    }
+    if let Some(version) = dependencies.get("quilt-loader") {
+        return Ok((ModLoader::Quilt, version.clone()))
+    }
+    if let Some(version) = dependencies.get("neoforge") {
+        return Ok((ModLoader::NeoForge, version.clone()))
+    }
+    let others: Vec<&String> = dependencies.keys().filter(|key| key.as_str() != "minecraft").collect();
+    Err(ModtoolError::Manifest(format!("The pack needs a mod loader ModTool can't install yet: {:?}", others)))
+}
+
+// Reads the profile a Modrinth pack describes, without saving anything. The id is left at 0.
+fn read_mrpack<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<(Profile, Vec<String>)> {
+    let index: MrpackIndex = {
+        let mut data = String::new();
+        archive.by_name("modrinth.index.json")?.read_to_string(&mut data)?;
+        serde_json::from_str(data.as_str())?
+    };
+    let mc_version = match index.dependencies.get("minecraft") {
+        Some(version) => version.clone(),
+        None => return Err(ModtoolError::Manifest(String::from("The pack doesn't say which Minecraft version it's for")))
+    };
+    let (loader, loader_version) = mrpack_loader(&index.dependencies)?;
+
+    let mut mods = Vec::new();
+    let mut skipped = Vec::new();
+    for file in index.files {
+        if file.env.as_ref().map(|env| env.client == "unsupported").unwrap_or(false) {
+            skipped.push(format!("{} (server only)", file.path));
+            continue
+        }
+        let filename = match file.path.strip_prefix("mods/") {
+            Some(filename) if !filename.contains('/') => String::from(filename),
+            _ => {
+                skipped.push(format!("{} (only mods are downloaded)", file.path));
+                continue
+            }
+        };
+        let url = match file.downloads.first() {
+            Some(url) => url.clone(),
+            None => {
+                skipped.push(format!("{} (no download url)", file.path));
+                continue
+            }
+        };
+        // The pack's versionId changes with every release of the pack, so the file itself stands in
+        // for the mod's version and only mods that actually changed are downloaded again
+        let version = file.hashes.get("sha1").or(file.hashes.get("sha512")).cloned().unwrap_or(filename.clone());
+        mods.push(Mod {
+            name: filename.trim_end_matches(".jar").to_string(),
+            provider: url_provider(url.as_str()),
+            url,
+            version,
+            size: file.fileSize as i32,
+            project: None,
+            projectID: None,
+            fileID: None,
+            filename: Some(filename),
+            manual: false,
+            sha1: file.hashes.get("sha1").cloned(),
+            sha256: None,
+            sha512: file.hashes.get("sha512").cloned()
+        });
+    }
+
+    let profile = Profile {
+        meta: ProfileMeta {
+            name: index.name,
+            loader,
+            version: mc_version,
+            id: 0,
+            loader_version: Some(loader_version),
+            overrides: None
+        },
+        mods
+    };
+    Ok((profile, skipped))
+}
+
+fn import_mrpack<R: Read + Seek>(mut archive: ZipArchive<R>) -> Result<ImportReport> {
+    let (profile, skipped) = read_mrpack(&mut archive)?;
+    // Client overrides go second, so they win over the shared ones
+    let profile = save_profile(&mut archive, &["overrides", "client-overrides"], profile.meta, profile.mods)?;
+    Ok(ImportReport { profile, skipped, unresolved: Vec::new() })
+}
+
+fn curseforge_loader(loaders: &[CurseForgeLoader]) -> Result<(ModLoader, String)> {
+    let loader = match loaders.iter().find(|loader| loader.primary).or(loaders.first()) {
+        Some(loader) => loader,
+        None => return Err(ModtoolError::Manifest(String::from("The pack doesn't say which mod loader it uses")))
+    };
+    match loader.id.split_once('-') {
+        Some(("forge", version)) => Ok((ModLoader::Forge, String::from(version))),
+        Some(("fabric", version)) => Ok((ModLoader::Fabric, String::from(version))),
+        Some(("quilt", version)) => Ok((ModLoader::Quilt, String::from(version))),
+        Some(("neoforge", version)) => Ok((ModLoader::NeoForge, String::from(version))),
+        _ => Err(ModtoolError::Manifest(format!("The pack needs a mod loader ModTool can't install yet: {}", loader.id)))
+    }
+}
+
+fn import_curseforge<R: Read + Seek>(mut archive: ZipArchive<R>) -> Result<ImportReport> {
+    let pack: CurseForgeManifest = {
+        let mut data = String::new();
+        archive.by_name("manifest.json")?.read_to_string(&mut data)?;
+        serde_json::from_str(data.as_str())?
+    };
+    let (loader, loader_version) = curseforge_loader(&pack.minecraft.modLoaders)?;
+
+    let mut mods = Vec::new();
+    let mut skipped = Vec::new();
+    let mut unresolved = Vec::new();
+    for file in pack.files {
+        let name = format!("CurseForge project {}", file.projectID);
+        if !file.required {
+            skipped.push(format!("{}, file {} (optional)", name, file.fileID));
+            continue
+        }
+        unresolved.push(format!("{}, file {}", name, file.fileID));
+        mods.push(Mod {
+            name,
+            url: String::new(),
+            version: String::new(),
+            provider: ModProvider::CurseForge,
+            size: 0,
+            project: None,
+            projectID: Some(file.projectID),
+            fileID: Some(file.fileID),
+            filename: None,
+            manual: false,
+            sha1: None,
+            sha256: None,
+            sha512: None
+        });
+    }
+
+    let overrides = pack.overrides.clone();
+    let profile = save_profile(&mut archive, &[overrides.as_str()], ProfileMeta {
+        name: match pack.version.is_empty() {
+            true => pack.name,
+            false => format!("{} {}", pack.name, pack.version)
+        },
+        loader,
+        version: pack.minecraft.version,
+        id: 0,
+        loader_version: Some(loader_version),
+        overrides: None
+    }, mods)?;
+    Ok(ImportReport { profile, skipped, unresolved })
+}
+
+// Gives the profile a local id, unpacks the override folders and adds it to the local manifest
+fn save_profile<R: Read + Seek>(archive: &mut ZipArchive<R>, prefixes: &[&str], mut meta: ProfileMeta, mods: Vec<Mod>) -> Result<Profile> {
+    meta.id = manifest::next_local_id()?;
+    let overrides = pack_dir(meta.id).join("overrides");
+    let mut extracted = 0;
+    for prefix in prefixes {
+        extracted += extract_prefix(archive, prefix, &overrides)?;
+    }
+    if extracted > 0 {
+        meta.overrides = Some(overrides.display().to_string());
+    }
+    let profile = Profile { meta, mods };
+    manifest::save_local_profile(profile.clone())?;
+    Ok(profile)
+}
+
+// Adds a modpack file to the local manifest
+pub fn import_pack(path: &Path) -> Result<ImportReport> {
+    let fhandle = File::open(path).map_err(|err| ModtoolError::io(path, err))?;
+    let archive = ZipArchive::new(fhandle)?;
+    if archive.file_names().any(|name| name == "modrinth.index.json") {
+        return import_mrpack(archive)
+    }
+    if archive.file_names().any(|name| name == "manifest.json") {
+        return import_curseforge(archive)
+    }
+    Err(ModtoolError::Manifest(format!("{} isn't a Modrinth or CurseForge modpack", path.display())))
+}
+
+// The loader version a pack should ask for: the one the profile pins, or else the one installed
+fn installed_loader_version(meta: &ProfileMeta) -> Result<String> {
+    if let Some(version) = meta.loader_version.as_ref() {
+        return Ok(version.clone())
+    }
+    match install::installed_loader(meta)? {
+        Some((_, version)) => Ok(version),
+        None => Err(ModtoolError::Manifest(format!("Couldn't tell which mod loader version {} uses, install it first", meta.name)))
+    }
+}
+
+fn loader_dependency(loader: ModLoader) -> &'static str {
+    match loader {
+        ModLoader::Forge => "forge",
+        ModLoader::Fabric => "fabric-loader",
+        ModLoader::Quilt => "quilt-loader",
+        ModLoader::NeoForge => "neoforge"
+    }
+}
+
+// Writes an installed profile as a Modrinth pack. Mods are listed with hashes of the installed
+// jars, and `config_dirs` (relative to the game directory) are added as overrides.
+pub fn export_mrpack(profile: &Profile, config_dirs: &[String], output: &Path) -> Result<ExportReport> {
+    let meta = &profile.meta;
+    let record = mods_dir::InstallRecord::load(meta.id)?
+        .ok_or(ModtoolError::Manifest(format!("{} isn't installed yet, so there's nothing to export", meta.name)))?;
+    let game_dir = PathBuf::from(meta.game_dir());
+    for dir in config_dirs {
+        if dir.is_empty() || !Path::new(dir).components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(ModtoolError::Manifest(format!("{} isn't a folder inside the game directory", dir)))
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut bundled = Vec::new();
+    for installed in record.files.iter() {
+        let path = Path::new(record.mods_dir.as_str()).join(&installed.filename);
+        // Older install records don't have urls, the profile still does
+        let url = match installed.url.is_empty() {
+            false => Some(installed.url.clone()),
+            true => profile.mods.iter()
+                .find(|modinfo| !modinfo.manual && install::mod_filename(&modinfo.filename, &modinfo.url).as_ref() == Some(&installed.filename))
+                .map(|modinfo| modinfo.url.clone())
+        };
+        let url = match url {
+            Some(url) if !url.is_empty() => url,
+            _ => {
+                bundled.push(installed.filename.clone());
+                continue
+            }
+        };
+        let mut hashes = HashMap::new();
+        hashes.insert(String::from("sha1"), file_sha1(&path).map_err(|err| ModtoolError::io(&path, err))?);
+        hashes.insert(String::from("sha512"), file_sha512(&path).map_err(|err| ModtoolError::io(&path, err))?);
+        files.push(MrpackFile {
+            path: format!("mods/{}", installed.filename),
+            hashes,
+            env: None,
+            downloads: vec![url],
+            fileSize: std::fs::metadata(&path).map_err(|err| ModtoolError::io(&path, err))?.len() as i64
+        });
+    }
+
+    let mut dependencies = HashMap::new();
+    dependencies.insert(String::from("minecraft"), meta.version.clone());
+    dependencies.insert(String::from(loader_dependency(meta.loader)), installed_loader_version(meta)?);
+    let index = MrpackIndex {
+        formatVersion: 1,
+        game: String::from("minecraft"),
+        name: meta.name.clone(),
+        versionId: Utc::now().format("%Y.%m.%d").to_string(),
+        files,
+        dependencies
+    };
+    let listed = index.files.len();
+
+    let fhandle = File::create(output).map_err(|err| ModtoolError::io(output, err))?;
+    let mut archive = zip::ZipWriter::new(fhandle);
+    archive.start_file("modrinth.index.json", FileOptions::default())?;
+    archive.write_all(serde_json::to_string_pretty(&index)?.as_bytes()).map_err(|err| ModtoolError::io(output, err))?;
+
+    let mut overrides = Vec::new();
+    for filename in bundled.iter() {
+        overrides.push(PathBuf::from("mods").join(filename));
+    }
+    for dir in config_dirs {
+        mods_dir::collect_files(&game_dir, Path::new(dir), &mut overrides)?;
+    }
+    for relative in overrides.iter() {
+        let name = format!("overrides/{}", relative.to_string_lossy().replace('\\', "/"));
+        archive.start_file(name, FileOptions::default())?;
+        let source = match relative.starts_with("mods") {
+            true => Path::new(record.mods_dir.as_str()).join(relative.strip_prefix("mods").unwrap()),
+            false => game_dir.join(relative)
+        };
+        let mut input = File::open(&source).map_err(|err| ModtoolError::io(&source, err))?;
+        std::io::copy(&mut input, &mut archive).map_err(|err| ModtoolError::io(output, err))?;
+    }
+    archive.finish()?;
+    Ok(ExportReport { listed, bundled, overrides: overrides.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn exported_pack_imports_as_the_same_files() {
+        test_support::home();
+        let mods_dir = test_support::scratch_dir("export-mods");
+        std::fs::write(mods_dir.join("sodium.jar"), b"sodium jar").unwrap();
+        std::fs::write(mods_dir.join("lithium.jar"), b"lithium jar").unwrap();
+        std::fs::write(mods_dir.join("handmade.jar"), b"no url for this one").unwrap();
+        let meta: ProfileMeta = serde_json::from_value(serde_json::json!({
+            "name": "Export", "loader": "Fabric", "version": "1.20.1", "id": 515151, "loader_version": "0.14.21"
+        })).unwrap();
+        let game_dir = PathBuf::from(meta.game_dir());
+        std::fs::create_dir_all(game_dir.join("export-config")).unwrap();
+        std::fs::write(game_dir.join("export-config").join("sodium.json"), b"{}").unwrap();
+
+        let installed = |name: &str, url: &str| mods_dir::InstalledFile {
+            name: String::from(name),
+            filename: format!("{}.jar", name),
+            version: String::from("1"),
+            sha1: file_sha1(&mods_dir.join(format!("{}.jar", name))).unwrap(),
+            url: String::from(url),
+            dependency: false
+        };
+        mods_dir::InstallRecord {
+            profile_id: meta.id,
+            profile_name: meta.name.clone(),
+            mods_dir: mods_dir.to_string_lossy().to_string(),
+            files: vec![
+                installed("sodium", "https://cdn.modrinth.com/data/AANobbMI/versions/1/sodium.jar"),
+                installed("lithium", "https://cdn.modrinth.com/data/gvQqBUqZ/versions/1/lithium.jar"),
+                installed("handmade", "")
+            ]
+        }.save().unwrap();
+
+        let profile = Profile { meta: meta.clone(), mods: Vec::new() };
+        let output = test_support::scratch_dir("export-pack").join("export.mrpack");
+        let report = export_mrpack(&profile, &[String::from("export-config")], &output).unwrap();
+        assert_eq!(report.listed, 2);
+        assert_eq!(report.bundled, vec!["handmade.jar"]);
+        assert_eq!(report.overrides, 2);
+
+        let mut archive = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let (imported, skipped) = read_mrpack(&mut archive).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(imported.meta.name, meta.name);
+        assert_eq!(imported.meta.version, meta.version);
+        assert!(imported.meta.loader == meta.loader);
+        assert_eq!(imported.meta.loader_version, meta.loader_version);
+        let mut filenames: Vec<String> = imported.mods.iter().map(|modinfo| modinfo.filename.clone().unwrap()).collect();
+        filenames.sort();
+        assert_eq!(filenames, vec!["lithium.jar", "sodium.jar"]);
+        for modinfo in imported.mods.iter() {
+            let path = mods_dir.join(modinfo.filename.as_ref().unwrap());
+            assert_eq!(modinfo.sha1.as_ref(), Some(&file_sha1(&path).unwrap()));
+            assert_eq!(modinfo.sha512.as_ref(), Some(&file_sha512(&path).unwrap()));
+            assert!(modinfo.url.ends_with(modinfo.filename.as_ref().unwrap().as_str()));
+        }
+
+        let mut bundled = String::new();
+        archive.by_name("overrides/mods/handmade.jar").unwrap().read_to_string(&mut bundled).unwrap();
+        assert_eq!(bundled, "no url for this one");
+        let mut config = String::new();
+        archive.by_name("overrides/export-config/sodium.json").unwrap().read_to_string(&mut config).unwrap();
+        assert_eq!(config, "{}");
+    }
+
+    #[test]
+    fn curseforge_pack_imports_with_its_ids_and_overrides() {
+        test_support::home();
+        let path = test_support::scratch_dir("curseforge-pack").join("pack.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        writer.start_file("manifest.json", FileOptions::default()).unwrap();
+        writer.write_all(serde_json::json!({
+            "name": "Cursed",
+            "version": "1.2",
+            "minecraft": {
+                "version": "1.19.2",
+                "modLoaders": [{ "id": "fabric-0.14.9" }, { "id": "forge-43.2.0", "primary": true }]
+            },
+            "files": [
+                { "projectID": 238222, "fileID": 4371807, "required": true },
+                { "projectID": 32274, "fileID": 3872807 },
+                { "projectID": 60089, "fileID": 4105741, "required": false }
+            ],
+            "overrides": "overrides"
+        }).to_string().as_bytes()).unwrap();
+        writer.start_file("overrides/config/jei.toml", FileOptions::default()).unwrap();
+        writer.write_all(b"enabled = true").unwrap();
+        writer.start_file("other/ignored.txt", FileOptions::default()).unwrap();
+        writer.write_all(b"not an override").unwrap();
+        writer.finish().unwrap();
+
+        let report = import_pack(&path).unwrap();
+        let meta = report.profile.meta.clone();
+        assert_eq!(meta.name, "Cursed 1.2");
+        assert_eq!(meta.version, "1.19.2");
+        assert!(meta.loader == ModLoader::Forge);
+        assert_eq!(meta.loader_version.as_deref(), Some("43.2.0"));
+        assert!(manifest::is_local(meta.id));
+
+        let ids: Vec<(Option<u64>, Option<u64>)> = report.profile.mods.iter().map(|modinfo| (modinfo.projectID, modinfo.fileID)).collect();
+        assert_eq!(ids, vec![(Some(238222), Some(4371807)), (Some(32274), Some(3872807))]);
+        assert!(report.profile.mods.iter().all(|modinfo| modinfo.provider == ModProvider::CurseForge && modinfo.url.is_empty()));
+        assert_eq!(report.unresolved.len(), 2);
+        assert_eq!(report.skipped, vec!["CurseForge project 60089, file 4105741 (optional)"]);
+
+        let overrides = PathBuf::from(meta.overrides.clone().unwrap());
+        assert_eq!(std::fs::read_to_string(overrides.join("config").join("jei.toml")).unwrap(), "enabled = true");
+        assert!(!overrides.join("other").exists());
+        assert!(manifest::load_local().unwrap().profiles.iter().any(|profile| profile.meta.id == meta.id));
+    }
+
+    #[test]
+    fn curseforge_loader_falls_back_to_the_first() {
+        let loaders = |ids: &[(&str, bool)]| -> Vec<CurseForgeLoader> {
+            ids.iter().map(|(id, primary)| CurseForgeLoader { id: String::from(*id), primary: *primary }).collect()
+        };
+        let (loader, version) = curseforge_loader(&loaders(&[("quilt-0.19.2", false), ("fabric-0.14.21", false)])).unwrap();
+        assert!(loader == ModLoader::Quilt);
+        assert_eq!(version, "0.19.2");
+        let (loader, version) = curseforge_loader(&loaders(&[("fabric-0.14.21", false), ("neoforge-20.4.237", true)])).unwrap();
+        assert!(loader == ModLoader::NeoForge);
+        assert_eq!(version, "20.4.237");
+        assert!(matches!(curseforge_loader(&[]), Err(ModtoolError::Manifest(_))));
+        assert!(matches!(curseforge_loader(&loaders(&[("liteloader-1.12", true)])), Err(ModtoolError::Manifest(message)) if message.contains("liteloader-1.12")));
+    }
+}
@@ -0,0 +1,19 @@
+// Manifest entries can name a project on a mod host instead of giving a url. These are
+// looked up right before installing, so a profile always gets the newest compatible files.
+
+use crate::*;
+use crate::error::{ModtoolError, Result};
+
+pub async fn resolve_profile(mut profile: Profile) -> Result<Profile> {
+    for modinfo in profile.mods.iter_mut() {
+        if !modinfo.url.is_empty() {
+            continue
+        }
+        *modinfo = match modinfo.provider {
+            ModProvider::Modrinth => modrinth::resolve(modinfo, &profile.meta).await?,
+            ModProvider::CurseForge => curseforge::resolve(modinfo).await?,
+            _ => return Err(ModtoolError::Manifest(format!("{} has no url", modinfo.name)))
+        };
+    }
+    Ok(profile)
+}
@@ -0,0 +1,153 @@
+// User settings, read from `settings.json` in CONFIG_DIR. Missing keys fall back to defaults,
+// so an empty `{}` file is valid.
+
+use crate::*;
+use std::sync::RwLock;
+
+pub static DEFAULT_MANIFEST: &str = "https://tallie.dev/modtool/manifest.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Settings {
+    // Each entry is an http(s) URL, a `file://` URL or a local path to a manifest JSON file.
+    // Profiles from every source are merged into a single list.
+    #[serde(default = "default_manifest_sources")]
+    pub manifest_sources: Vec<String>,
+    // Install every profile into its own game directory instead of the shared `.minecraft`
+    #[serde(default = "default_true")]
+    pub isolate_profiles: bool,
+    // Downloaded mods are kept in CACHE_DIR/mods up to this size, least recently used go first
+    #[serde(default = "default_cache_limit_mb")]
+    pub cache_limit_mb: u64,
+    // How long to wait for a server to accept a connection, and then for an API response or between
+    // chunks of a download
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_read_timeout_secs")]
+    pub read_timeout_secs: u64,
+    // Extra attempts for a download that fails with a network error, with exponential backoff
+    #[serde(default = "default_download_retries")]
+    pub download_retries: u32,
+    // How many mods download at the same time
+    #[serde(default = "default_parallel_downloads")]
+    pub parallel_downloads: usize,
+    // Base url of the Modrinth v2 API, can point at a mirror or a local test server
+    #[serde(default = "default_modrinth_api")]
+    pub modrinth_api: String,
+    // Needed for CurseForge mods, from https://console.curseforge.com/
+    #[serde(default)]
+    pub curseforge_api_key: Option<String>,
+    #[serde(default = "default_curseforge_api")]
+    pub curseforge_api: String,
+    // Base urls of the Fabric Meta v2 and Quilt Meta v3 APIs, which loader profiles are fetched from
+    #[serde(default = "default_fabric_meta")]
+    pub fabric_meta: String,
+    #[serde(default = "default_quilt_meta")]
+    pub quilt_meta: String,
+    // Base url of the NeoForge maven, which serves both the version list and the installers
+    #[serde(default = "default_neoforge_maven")]
+    pub neoforge_maven: String,
+    // Base url of the Adoptium v3 API, where Java runtimes come from when none installed will do
+    #[serde(default = "default_java_runtime_api")]
+    pub java_runtime_api: String
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_cache_limit_mb() -> u64 {
+    2048
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    15
+}
+
+fn default_read_timeout_secs() -> u64 {
+    30
+}
+
+fn default_download_retries() -> u32 {
+    3
+}
+
+fn default_parallel_downloads() -> usize {
+    4
+}
+
+fn default_modrinth_api() -> String {
+    String::from("https://api.modrinth.com/v2")
+}
+
+fn default_curseforge_api() -> String {
+    String::from("https://api.curseforge.com")
+}
+
+fn default_fabric_meta() -> String {
+    String::from("https://meta.fabricmc.net/v2")
+}
+
+fn default_quilt_meta() -> String {
+    String::from("https://meta.quiltmc.org/v3")
+}
+
+fn default_neoforge_maven() -> String {
+    String::from("https://maven.neoforged.net")
+}
+
+fn default_java_runtime_api() -> String {
+    String::from("https://api.adoptium.net/v3")
+}
+
+fn default_manifest_sources() -> Vec<String> {
+    vec![String::from(DEFAULT_MANIFEST)]
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            manifest_sources: default_manifest_sources(),
+            isolate_profiles: true,
+            cache_limit_mb: default_cache_limit_mb(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            read_timeout_secs: default_read_timeout_secs(),
+            download_retries: default_download_retries(),
+            parallel_downloads: default_parallel_downloads(),
+            modrinth_api: default_modrinth_api(),
+            curseforge_api_key: None,
+            curseforge_api: default_curseforge_api(),
+            fabric_meta: default_fabric_meta(),
+            quilt_meta: default_quilt_meta(),
+            neoforge_maven: default_neoforge_maven(),
+            java_runtime_api: default_java_runtime_api()
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> std::path::PathBuf {
+        Path::new(CONFIG_DIR.as_str()).join("settings.json")
+    }
+
+    pub fn load() -> Settings {
+        let data = match std::fs::read_to_string(Settings::path()) {
+            Ok(data) => data,
+            Err(_) => return Settings::default()
+        };
+        match serde_json::from_str(data.as_str()) {
+            Ok(settings) => settings,
+            Err(err) => {
+                log::warn!("Ignoring invalid {}: {}", Settings::path().display(), err);
+                Settings::default()
+            }
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref SETTINGS: RwLock<Settings> = RwLock::new(Settings::load());
+}
+
+pub fn settings() -> Settings {
+    SETTINGS.read().unwrap().clone()
+}
@@ -0,0 +1,114 @@
+// Shared by the unit tests: a throwaway HOME for CONFIG_DIR, CACHE_DIR and MC_DATA, a lock for tests
+// that change process-wide state, and a tiny HTTP server to stand in for Modrinth, CurseForge, the
+// loader metas and Adoptium.
+
+use crate::settings::{self, Settings};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+static INIT: Once = Once::new();
+
+lazy_static! {
+    static ref GLOBALS: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+// Points HOME (and APPDATA) at an empty folder. Has to run before anything reads the lazy statics.
+pub fn home() -> PathBuf {
+    let home = std::env::temp_dir().join(format!("modtool-test-{}", std::process::id()));
+    INIT.call_once(|| {
+        let _ = std::fs::remove_dir_all(&home);
+        std::fs::create_dir_all(home.join(".minecraft").join("versions")).unwrap();
+        std::env::set_var("HOME", &home);
+        std::env::set_var("APPDATA", &home);
+    });
+    home
+}
+
+// Cargo runs tests in parallel, so tests that change SETTINGS or launcher_profiles.json hold this
+// while they run. The settings are put back as they were when it's dropped.
+pub struct GlobalsGuard {
+    _lock: tokio::sync::MutexGuard<'static, ()>,
+    settings: Settings
+}
+
+impl Drop for GlobalsGuard {
+    fn drop(&mut self) {
+        *settings::SETTINGS.write().unwrap() = self.settings.clone();
+    }
+}
+
+pub async fn lock_globals() -> GlobalsGuard {
+    let lock = GLOBALS.lock().await;
+    GlobalsGuard { _lock: lock, settings: settings::settings() }
+}
+
+// For tests that aren't async
+pub fn blocking_lock_globals() -> GlobalsGuard {
+    let lock = GLOBALS.blocking_lock();
+    GlobalsGuard { _lock: lock, settings: settings::settings() }
+}
+
+// A folder of its own under the test HOME
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = home().join("scratch").join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[derive(Clone)]
+pub struct Route {
+    pub status: u16,
+    pub body: Vec<u8>
+}
+
+// Serves fixed responses by path (the query string is ignored), 404 for anything else.
+// Every requested path is kept in `requests`.
+pub struct MockServer {
+    pub url: String,
+    pub requests: Arc<Mutex<Vec<String>>>
+}
+
+impl MockServer {
+    pub async fn start(routes: Vec<(&str, u16, Vec<u8>)>) -> MockServer {
+        let routes: HashMap<String, Route> = routes.into_iter()
+            .map(|(path, status, body)| (String::from(path), Route { status, body }))
+            .collect();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let routes = routes.clone();
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(read) => request.extend_from_slice(&buf[..read])
+                        }
+                    }
+                    let request = String::from_utf8_lossy(&request).to_string();
+                    let target = request.split_whitespace().nth(1).unwrap_or("/");
+                    let path = target.split('?').next().unwrap_or(target).to_string();
+                    log.lock().unwrap().push(path.clone());
+                    let route = routes.get(&path).cloned().unwrap_or(Route { status: 404, body: b"not found".to_vec() });
+                    let head = format!("HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", route.status, route.body.len());
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(&route.body).await;
+                });
+            }
+        });
+        MockServer { url, requests }
+    }
+
+    pub fn json(value: serde_json::Value) -> Vec<u8> {
+        serde_json::to_vec(&value).unwrap()
+    }
+}